@@ -8,6 +8,294 @@ pub struct Settings {
     pub output_dir: PathBuf,
     pub output_format: String,
     pub dark_theme: bool,
+    #[serde(default)]
+    pub run_in_background: bool,
+    #[serde(default)]
+    pub notifications: NotificationSettings,
+    #[serde(default)]
+    pub rate_limit: Option<String>,
+    #[serde(default)]
+    pub schedule: Vec<ScheduleWindow>,
+    #[serde(default)]
+    pub network: NetworkSettings,
+    #[serde(default)]
+    pub cookies: CookieSource,
+    #[serde(default)]
+    pub binaries: BinarySettings,
+    #[serde(default)]
+    pub subtitles: SubtitleOptions,
+    #[serde(default)]
+    pub sponsorblock: SponsorBlockSettings,
+    #[serde(default)]
+    pub loudness: LoudnessSettings,
+    /// Run in order on every finished download
+    #[serde(default)]
+    pub post_processing: Vec<PostProcessStep>,
+    #[serde(default)]
+    pub queue_sort: QueueSort,
+}
+
+/// Step of the post-processing pipeline, text fields may contain placeholders
+/// like `{path}`, `{dir}`, `{name}`, `{ext}`, `{title}`, `{id}` and `{url}`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum PostProcessStep {
+    /// Re-encodes into one of [`AUDIO_FORMATS`] except `best`
+    Convert { format: String },
+    /// Removes silence at the start and end
+    TrimSilence,
+    Normalize(LoudnessSettings),
+    /// Sets metadata tags
+    Tag { tags: Vec<(String, String)> },
+    /// Moves the file into a directory
+    Move { dir: String },
+    /// Runs a program, a non-zero exit code fails the download
+    Command { program: String, args: Vec<String> },
+}
+
+impl PostProcessStep {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PostProcessStep::Convert { .. } => "Convert",
+            PostProcessStep::TrimSilence => "Trim silence",
+            PostProcessStep::Normalize(_) => "Normalize",
+            PostProcessStep::Tag { .. } => "Tag",
+            PostProcessStep::Move { .. } => "Move",
+            PostProcessStep::Command { .. } => "Command",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct LoudnessSettings {
+    pub mode: LoudnessMode,
+    /// Integrated loudness in LUFS
+    pub target: f32,
+    /// Maximum true peak in dBTP
+    pub true_peak: f32,
+}
+
+impl Default for LoudnessSettings {
+    fn default() -> Self {
+        Self {
+            mode: LoudnessMode::Off,
+            target: -14.0,
+            true_peak: -1.0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub enum LoudnessMode {
+    #[default]
+    Off,
+    /// Re-encode to the target loudness (EBU R128, two passes)
+    Normalize,
+    /// Only write ReplayGain tags
+    ReplayGain,
+}
+
+/// Loudness of a file as measured by ffmpeg's `loudnorm` filter.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct Loudness {
+    /// LUFS
+    pub integrated: f32,
+    /// dBTP
+    pub true_peak: f32,
+    /// LU
+    pub range: f32,
+    pub threshold: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct SponsorBlockSettings {
+    pub enabled: bool,
+    /// Categories cut out of the download
+    pub remove: Vec<String>,
+    /// Categories marked as chapters
+    pub mark: Vec<String>,
+    pub api_url: String,
+}
+
+impl Default for SponsorBlockSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            remove: vec!["sponsor".into()],
+            mark: Vec::new(),
+            api_url: SPONSORBLOCK_API.into(),
+        }
+    }
+}
+
+pub const SPONSORBLOCK_API: &str = "https://sponsor.ajay.app";
+
+/// Segment categories accepted by yt-dlp's `--sponsorblock-remove` and `--sponsorblock-mark`.
+pub const SPONSORBLOCK_CATEGORIES: [&str; 8] = [
+    "sponsor",
+    "intro",
+    "outro",
+    "selfpromo",
+    "preview",
+    "filler",
+    "interaction",
+    "music_offtopic",
+];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct SubtitleOptions {
+    pub manual: bool,
+    pub auto_generated: bool,
+    /// yt-dlp language codes, all available languages if empty, required for auto-generated captions
+    pub languages: Vec<String>,
+    pub format: SubtitleFormat,
+    /// Embed into video outputs instead of writing sidecar files, audio outputs always get sidecar files
+    pub embed: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub enum SubtitleFormat {
+    #[default]
+    Srt,
+    Vtt,
+    Ass,
+}
+
+impl SubtitleFormat {
+    pub const ALL: [SubtitleFormat; 3] = [SubtitleFormat::Srt, SubtitleFormat::Vtt, SubtitleFormat::Ass];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SubtitleFormat::Srt => "srt",
+            SubtitleFormat::Vtt => "vtt",
+            SubtitleFormat::Ass => "ass",
+        }
+    }
+}
+
+/// Subtitle languages a video offers.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SubtitleLanguages {
+    pub manual: Vec<String>,
+    pub auto_generated: Vec<String>,
+}
+
+/// Where an external tool is run from.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub enum BinarySource {
+    #[default]
+    Bundled,
+    /// First match on `PATH`
+    System,
+    Custom(PathBuf),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct BinarySettings {
+    pub yt_dlp: BinarySource,
+    pub ffmpeg: BinarySource,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Tool {
+    YtDlp,
+    Ffmpeg,
+    /// Always taken from the same place as ffmpeg
+    Ffprobe,
+    /// Always taken from the same place as ffmpeg
+    Ffplay,
+}
+
+impl Tool {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tool::YtDlp => "yt-dlp",
+            Tool::Ffmpeg => "ffmpeg",
+            Tool::Ffprobe => "ffprobe",
+            Tool::Ffplay => "ffplay",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BinaryCheck {
+    pub tool: Tool,
+    pub path: Option<PathBuf>,
+    pub version: Result<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Diagnostics {
+    pub app_version: String,
+    pub os: String,
+    pub binaries: Vec<BinaryCheck>,
+    pub output_dir: PathBuf,
+    pub output_dir_writable: bool,
+    pub config_dir: PathBuf,
+    pub settings_file: PathBuf,
+    pub data_dir: PathBuf,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub enum CookieSource {
+    #[default]
+    None,
+    /// Netscape cookies.txt imported into the app data dir
+    File,
+    Browser {
+        browser: String,
+        profile: Option<String>,
+    },
+}
+
+/// Browsers supported by yt-dlp's `--cookies-from-browser`.
+pub const COOKIE_BROWSERS: [&str; 9] = [
+    "brave", "chrome", "chromium", "edge", "firefox", "opera", "safari", "vivaldi", "whale",
+];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct NetworkSettings {
+    pub proxy: Option<String>,
+    pub ip_version: IpVersion,
+    pub source_address: Option<String>,
+    pub socket_timeout: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub enum IpVersion {
+    #[default]
+    Any,
+    V4,
+    V6,
+}
+
+/// Time of day range in `HH:MM` format, `end` may be before `start` to span midnight.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ScheduleWindow {
+    pub start: String,
+    pub end: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub download_finished: bool,
+    pub download_failed: bool,
+    pub queue_finished: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            download_finished: true,
+            download_failed: true,
+            queue_finished: true,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
@@ -18,6 +306,17 @@ pub struct Metadata {
     pub duration: String,
     pub thumbnail: String,
     pub loading: bool,
+    #[serde(default)]
+    pub chapters: Vec<Chapter>,
+    #[serde(default)]
+    pub uploader: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct Chapter {
+    pub title: String,
+    pub start_time: f64,
+    pub end_time: f64,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -27,6 +326,163 @@ pub struct MetadataArgs<'a> {
 }
 
 #[derive(Serialize, Deserialize)]
+pub struct ValueArgs<T> {
+    pub value: T,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UrlArgs {
+    pub url: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ToolArgs {
+    pub tool: Tool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct IdArgs {
+    pub id: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct IdsArgs {
+    pub ids: Vec<String>,
+}
+
+/// File format the queue is exported to and imported from.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum QueueFileFormat {
+    /// Every field of the downloads
+    Json,
+    /// The metadata and the commonly changed options
+    Csv,
+    /// One URL per line
+    Text,
+}
+
+impl QueueFileFormat {
+    pub const ALL: [QueueFileFormat; 3] = [QueueFileFormat::Json, QueueFileFormat::Csv, QueueFileFormat::Text];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            QueueFileFormat::Json => "JSON",
+            QueueFileFormat::Csv => "CSV",
+            QueueFileFormat::Text => "URL list",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            QueueFileFormat::Json => "json",
+            QueueFileFormat::Csv => "csv",
+            QueueFileFormat::Text => "txt",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct QueueFileArgs {
+    pub format: QueueFileFormat,
+}
+
+/// Entry of an imported file that was not added to the queue.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SkippedEntry {
+    /// The URL, or the line if there is none
+    pub entry: String,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct ImportReport {
+    /// URLs of the added downloads
+    pub added: Vec<String>,
+    pub skipped: Vec<SkippedEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FormatArgs {
+    pub ids: Vec<String>,
+    pub format: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MoveArgs {
+    pub id: String,
+    pub index: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PriorityArgs {
+    pub id: String,
+    pub high: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct OffsetArgs {
+    pub offset: i32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PathArgs {
+    pub path: PathBuf,
+}
+
+/// Audio file found in the output directory.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LibraryItem {
+    pub path: PathBuf,
+    pub tags: LibraryTags,
+    /// Seconds
+    pub duration: Option<u32>,
+    /// Bytes
+    pub size: u64,
+    /// Seconds since the Unix epoch
+    pub modified: u64,
+    /// URL of the video the file was downloaded from, if known
+    pub source: Option<String>,
+}
+
+impl LibraryItem {
+    /// Name the library groups by, the artist or else the album artist
+    pub fn group(&self) -> &str {
+        [&self.tags.artist, &self.tags.album_artist]
+            .into_iter()
+            .find(|a| !a.trim().is_empty())
+            .map_or("Unknown artist", |a| a.as_str())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct LibraryTags {
+    pub title: String,
+    pub artist: String,
+    pub album_artist: String,
+    pub album: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RetagArgs {
+    pub path: PathBuf,
+    pub tags: LibraryTags,
+}
+
+/// What ffplay is currently playing.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Playback {
+    /// ID of the download
+    pub id: String,
+    /// Streaming the source instead of playing the downloaded file
+    pub preview: bool,
+    /// Seconds into the audio when this event was sent
+    pub position: u32,
+    /// Seconds after which playback stops, only set for previews
+    pub end: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DownloadStateArgs {
     pub id: String,
     pub state: DownloadState,
@@ -36,6 +492,233 @@ pub struct DownloadStateArgs {
 pub struct Download {
     pub metadata: Metadata,
     pub download_state: DownloadState,
+    #[serde(default)]
+    pub options: DownloadOptions,
+    #[serde(default)]
+    pub output_path: Option<PathBuf>,
+    /// URL of the subscription that enqueued this download
+    #[serde(default)]
+    pub subscription: Option<String>,
+    /// Measured before normalizing, of the first file if there are several
+    #[serde(default)]
+    pub loudness: Option<Loudness>,
+    /// Downloaded before the other items by `download_all`
+    #[serde(default)]
+    pub high_priority: bool,
+    /// When it was added to the queue, in seconds since the Unix epoch
+    #[serde(default)]
+    pub added: u64,
+}
+
+/// How the queue is displayed, the download order is not affected.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+pub struct QueueSort {
+    pub key: SortKey,
+    pub descending: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+pub enum SortKey {
+    /// The order of the queue itself
+    #[default]
+    Queue,
+    Added,
+    Title,
+    Duration,
+    Status,
+}
+
+impl SortKey {
+    pub const ALL: [SortKey; 5] = [
+        SortKey::Queue,
+        SortKey::Added,
+        SortKey::Title,
+        SortKey::Duration,
+        SortKey::Status,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SortKey::Queue => "Queue order",
+            SortKey::Added => "Added",
+            SortKey::Title => "Title",
+            SortKey::Duration => "Duration",
+            SortKey::Status => "Status",
+        }
+    }
+}
+
+impl QueueSort {
+    /// Compares by the key only, the caller breaks ties by the queue order.
+    pub fn compare(&self, a: &Download, b: &Download) -> std::cmp::Ordering {
+        let ordering = match self.key {
+            SortKey::Queue => std::cmp::Ordering::Equal,
+            SortKey::Added => a.added.cmp(&b.added),
+            SortKey::Title => a.metadata.title.to_lowercase().cmp(&b.metadata.title.to_lowercase()),
+            SortKey::Duration => parse_timestamp(&a.metadata.duration).cmp(&parse_timestamp(&b.metadata.duration)),
+            SortKey::Status => a.download_state.rank().cmp(&b.download_state.rank()),
+        };
+        if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+/// Groups of [`DownloadState`]s the queue can be filtered by.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+pub enum StateFilter {
+    #[default]
+    All,
+    Idle,
+    Active,
+    Finished,
+    Failed,
+}
+
+impl StateFilter {
+    pub const ALL: [StateFilter; 5] = [
+        StateFilter::All,
+        StateFilter::Idle,
+        StateFilter::Active,
+        StateFilter::Finished,
+        StateFilter::Failed,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StateFilter::All => "All",
+            StateFilter::Idle => "Idle",
+            StateFilter::Active => "Active",
+            StateFilter::Finished => "Finished",
+            StateFilter::Failed => "Failed",
+        }
+    }
+
+    pub fn matches(&self, state: &DownloadState) -> bool {
+        match self {
+            StateFilter::All => true,
+            StateFilter::Idle => *state == DownloadState::Idle,
+            StateFilter::Active => matches!(
+                state,
+                DownloadState::Loading(_)
+                    | DownloadState::MetadataLoading
+                    | DownloadState::Scheduled
+                    | DownloadState::PostProcessing(_)
+            ),
+            StateFilter::Finished => *state == DownloadState::Finished,
+            StateFilter::Failed => matches!(state, DownloadState::Failure | DownloadState::VerificationFailed),
+        }
+    }
+}
+
+/// Per-download overrides of the global [`Settings`].
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DownloadOptions {
+    pub output_format: Option<String>,
+    #[serde(default)]
+    pub output_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub subtitles: Option<SubtitleOptions>,
+    /// One file per chapter instead of one for the whole video
+    #[serde(default)]
+    pub split_chapters: bool,
+    /// Only these parts are downloaded, each into its own file
+    #[serde(default)]
+    pub time_ranges: Vec<TimeRange>,
+    #[serde(default)]
+    pub sponsorblock: Option<SponsorBlockSettings>,
+    #[serde(default)]
+    pub loudness: Option<LoudnessSettings>,
+}
+
+/// Part of a video in seconds, `end` is the end of the video if missing.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TimeRange {
+    pub start: u32,
+    pub end: Option<u32>,
+}
+
+impl TimeRange {
+    pub fn is_valid(&self) -> bool {
+        self.end.is_none_or(|end| end > self.start)
+    }
+
+    /// Length in seconds, `duration` is the length of the whole video.
+    pub fn length(&self, duration: u32) -> u32 {
+        self.end.unwrap_or(duration).min(duration).saturating_sub(self.start)
+    }
+}
+
+/// Parses `ss`, `m:ss` or `h:mm:ss` into seconds.
+pub fn parse_timestamp(timestamp: &str) -> Option<u32> {
+    let timestamp = timestamp.trim();
+    if timestamp.is_empty() || timestamp.split(':').count() > 3 {
+        return None;
+    }
+    timestamp
+        .split(':')
+        .try_fold(0, |seconds, part| Some(seconds * 60 + part.parse::<u32>().ok()?))
+}
+
+/// Formats seconds as `m:ss` or `h:mm:ss`.
+pub fn format_timestamp(seconds: u32) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DownloadOptionsArgs {
+    pub id: String,
+    pub options: DownloadOptions,
+}
+
+/// A channel or playlist whose new uploads are enqueued automatically.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Subscription {
+    pub url: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub options: DownloadOptions,
+    #[serde(default)]
+    pub filter: SubscriptionFilter,
+    /// IDs of the videos already downloaded or skipped
+    #[serde(default)]
+    pub seen: Vec<String>,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SubscriptionFilter {
+    /// Case-insensitive substring of the title
+    pub title_contains: Option<String>,
+    /// Durations in seconds
+    pub min_duration: Option<u32>,
+    pub max_duration: Option<u32>,
+}
+
+/// Audio formats accepted by yt-dlp's `--audio-format`.
+pub const AUDIO_FORMATS: [&str; 9] = [
+    "best", "aac", "alac", "flac", "m4a", "mp3", "opus", "vorbis", "wav",
+];
+
+/// Containers the video is kept in instead of extracting the audio, subtitles can be embedded into them.
+pub const VIDEO_FORMATS: [&str; 3] = ["mkv", "mp4", "webm"];
+
+pub fn is_video_format(format: &str) -> bool {
+    VIDEO_FORMATS.contains(&format)
 }
 
 impl PartialEq for Download {
@@ -52,6 +735,24 @@ pub enum DownloadState {
     Finished,
     Failure,
     MetadataLoading,
+    Scheduled,
+    /// Name of the running post-processing step
+    PostProcessing(String),
+    /// Downloaded, but the file is missing, broken or of the wrong length
+    VerificationFailed,
+}
+
+impl DownloadState {
+    /// Position when sorting by status, the progress is ignored so rows don't move while downloading.
+    pub fn rank(&self) -> u8 {
+        match self {
+            DownloadState::Loading(_) | DownloadState::PostProcessing(_) | DownloadState::MetadataLoading => 0,
+            DownloadState::Scheduled => 1,
+            DownloadState::Idle => 2,
+            DownloadState::Failure | DownloadState::VerificationFailed => 3,
+            DownloadState::Finished => 4,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -60,6 +761,19 @@ pub struct DownloadEvent {
     pub progress: u8,
 }
 
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LaunchArgs {
+    pub links: Vec<AddUrlArgs>,
+    pub start: bool,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AddUrlArgs {
+    pub url: String,
+    pub options: DownloadOptions,
+}
+
 #[derive(Error, Serialize, Deserialize, Debug)]
 pub enum AddLinkError {
     #[error("Video has already been added")]
@@ -70,6 +784,54 @@ pub enum AddLinkError {
     ClipboardRead,
 }
 
+#[derive(Error, Serialize, Deserialize, Debug)]
+pub enum DeepLinkError {
+    #[error("Malformed yaydl link")]
+    Malformed,
+    #[error("Unknown yaydl link action: {0}")]
+    UnknownAction(String),
+    #[error("yaydl link is missing the url parameter")]
+    MissingUrl,
+    #[error("Unsupported output format: {0}")]
+    UnsupportedFormat(String),
+}
+
+#[derive(Error, Serialize, Deserialize, Debug)]
+pub enum SettingsError {
+    #[error("Invalid rate limit: {0}")]
+    InvalidRateLimit(String),
+    #[error("Invalid time: {0}")]
+    InvalidTime(String),
+    #[error("Invalid proxy: {0}")]
+    InvalidProxy(String),
+    #[error("Invalid source address: {0}")]
+    InvalidSourceAddress(String),
+    #[error("Socket timeout must be greater than zero")]
+    InvalidSocketTimeout,
+    #[error("Not a Netscape cookies.txt file")]
+    InvalidCookieFile,
+    #[error("Importing the cookie file failed")]
+    CookieImportFailed,
+    #[error("Unsupported browser: {0}")]
+    UnsupportedBrowser(String),
+    #[error("Unknown SponsorBlock category: {0}")]
+    UnknownSponsorBlockCategory(String),
+    #[error("Invalid SponsorBlock API URL: {0}")]
+    InvalidSponsorBlockApi(String),
+    #[error("{0} not found")]
+    BinaryNotFound(String),
+    #[error("{0} is not a working binary")]
+    InvalidBinary(String),
+    #[error("Invalid post-processing step: {0}")]
+    InvalidPostProcessStep(String),
+    #[error("Auto-generated captions need languages, requesting all of them is rate-limited")]
+    MissingCaptionLanguages,
+    #[error("Unsupported format: {0}")]
+    UnsupportedFormat(String),
+    #[error("Turn off loudness normalization before adding a Normalize step, files would be normalized twice")]
+    DoubleNormalization,
+}
+
 #[derive(Error, Serialize, Deserialize, Debug)]
 pub enum MetadataError {
     #[error("Retreiving metadata failed")]
@@ -90,6 +852,58 @@ pub enum UpdateError {
     DownloadAndInstallFailed,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct YtDlpVersion {
+    pub installed: String,
+    pub latest: Option<String>,
+    /// Whether the binary was updated at runtime instead of being the bundled sidecar
+    pub managed: bool,
+}
+
+#[derive(Error, Serialize, Deserialize, Debug)]
+pub enum SubscriptionError {
+    #[error("Not a channel or playlist URL")]
+    InvalidUrl,
+    #[error("Already subscribed")]
+    AlreadySubscribed,
+    #[error("Listing the uploads of {0} failed")]
+    ListingFailed(String),
+    #[error("Saving subscriptions failed")]
+    SaveFailed,
+}
+
+#[derive(Error, Serialize, Deserialize, Debug)]
+pub enum QueueFileError {
+    #[error("The queue is empty")]
+    NothingToExport,
+    #[error("Writing {0} failed")]
+    WriteFailed(String),
+    #[error("Reading {0} failed")]
+    ReadFailed(String),
+    #[error("{0} is not a queue file")]
+    ParsingFailed(String),
+}
+
+#[derive(Error, Serialize, Deserialize, Debug)]
+pub enum YtDlpUpdateError {
+    #[error("Checking the latest yt-dlp version failed")]
+    VersionCheckFailed,
+    #[error("Downloading yt-dlp failed")]
+    DownloadFailed,
+    #[error("Downloaded yt-dlp doesn't match its checksum")]
+    ChecksumMismatch,
+    #[error("Installing yt-dlp failed")]
+    InstallFailed,
+    #[error("The new yt-dlp failed its self-test and was rolled back")]
+    SelfTestFailed,
+    #[error("There is no yt-dlp version to roll back to")]
+    NoRollback,
+    #[error("Only the bundled yt-dlp can be updated")]
+    NotBundled,
+    #[error("yt-dlp {0} is already the latest version")]
+    AlreadyUpToDate(String),
+}
+
 #[derive(Error, Serialize, Deserialize, Debug)]
 pub enum YaydlError {
     #[error(transparent)]
@@ -97,13 +911,57 @@ pub enum YaydlError {
     #[error("Shell error: {0}")]
     TauriShellError(String),
     #[error(transparent)]
+    DeepLinkError(#[from] DeepLinkError),
+    #[error(transparent)]
     MetadataError(#[from] MetadataError),
     #[error(transparent)]
     UpdateError(#[from] UpdateError),
+    #[error(transparent)]
+    SettingsError(#[from] SettingsError),
+    #[error(transparent)]
+    YtDlpUpdateError(#[from] YtDlpUpdateError),
+    #[error(transparent)]
+    SubscriptionError(#[from] SubscriptionError),
+    #[error(transparent)]
+    QueueFileError(#[from] QueueFileError),
     #[error("Failed to convert output to UTF-8")]
     Utf8Conversion,
     #[error("Unsupported operating system")]
     UnsupportedOs,
     #[error("Folder selection failed")]
     FolderSelectionFailed,
+    #[error("File selection failed")]
+    FileSelectionFailed,
+    #[error("This video requires signing in, import cookies to access it")]
+    AuthRequired,
+    #[error("Download was cancelled")]
+    DownloadCancelled,
+    #[error("Download was removed from the queue")]
+    DownloadRemoved,
+    #[error("Download failed")]
+    DownloadFailed,
+    #[error("Tagging the chapter tracks failed")]
+    TaggingFailed,
+    #[error("Time range ends before it starts")]
+    InvalidTimeRange,
+    #[error("Loudness normalization failed")]
+    LoudnessFailed,
+    #[error("Verification failed, {0}")]
+    VerificationFailed(String),
+    #[error("Copying to the clipboard failed")]
+    ClipboardWriteFailed,
+    #[error("Nothing to play")]
+    NothingToPlay,
+    #[error("{0} is not in the library")]
+    NotInLibrary(String),
+    #[error("Reading {0} failed")]
+    ProbeFailed(String),
+    #[error("Deleting {0} failed")]
+    DeleteFailed(String),
+    #[error("Playback failed")]
+    PlaybackFailed,
+    #[error("Post-processing step {0} failed: {1}")]
+    PostProcessingFailed(String, String),
+    #[error("Connection test failed: {0}")]
+    ConnectionTestFailed(String),
 }
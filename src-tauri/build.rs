@@ -1,52 +1,134 @@
+use std::collections::HashMap;
 use std::env;
 use std::fs::{self, create_dir_all, File};
-use std::io::{self, Write};
+use std::io;
 use std::path::{Path, PathBuf};
 #[cfg(target_os = "linux")]
 use std::fs::Permissions;
 #[cfg(target_os = "linux")]
 use std::os::unix::fs::PermissionsExt;
 
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
 use tar::Archive;
 use xz2::read::XzDecoder;
 
-const LINUX_URL: &str = "https://github.com/yt-dlp/yt-dlp/releases/latest/download/yt-dlp_linux";
-const WINDOWS_URL: &str = "https://github.com/yt-dlp/yt-dlp/releases/latest/download/yt-dlp.exe";
-const FFMPEG_WIN_URL: &str = "https://github.com/yt-dlp/FFmpeg-Builds/releases/download/latest/ffmpeg-master-latest-win32-gpl.zip";
-const FFMPEG_LINUX_URL: &str = "https://github.com/yt-dlp/FFmpeg-Builds/releases/download/latest/ffmpeg-master-latest-linux64-gpl.tar.xz";
+const MANIFEST: &str = "./binaries.toml";
 const DOWNLOAD_DIR: &str = "./binaries";
+/// Copy of the manifest the current binaries were provisioned from
+const PROVISIONED: &str = "./binaries/provisioned.toml";
+/// Directory with pre-downloaded files for offline builds
+const BINARIES_DIR_ENV: &str = "YAYDL_BINARIES_DIR";
+
+#[derive(Deserialize)]
+struct Artifact {
+    version: String,
+    targets: HashMap<String, Source>,
+}
+
+#[derive(Deserialize)]
+struct Source {
+    url: String,
+    sha256: String,
+}
 
 fn main() -> io::Result<()> {
     let os = env::consts::OS;
     let target_triple = get_target_triple();
     let binaries = vec!["yt-dlp", "ffmpeg", "ffprobe", "ffplay"];
 
-    println!("cargo:rerun-if-changed=./binaries/{}{}", binaries[0], target_triple);
-    println!("cargo:rerun-if-changed=./binaries/{}{}", binaries[1], target_triple);
-    println!("cargo:rerun-if-changed=./binaries/{}{}", binaries[2], target_triple);
-    println!("cargo:rerun-if-changed=./binaries/{}{}", binaries[3], target_triple);
+    println!("cargo:rerun-if-changed={MANIFEST}");
+    println!("cargo:rerun-if-env-changed={BINARIES_DIR_ENV}");
+    println!("cargo:rerun-if-changed=./binaries/{}-{}", binaries[0], target_triple);
+    println!("cargo:rerun-if-changed=./binaries/{}-{}", binaries[1], target_triple);
+    println!("cargo:rerun-if-changed=./binaries/{}-{}", binaries[2], target_triple);
+    println!("cargo:rerun-if-changed=./binaries/{}-{}", binaries[3], target_triple);
 
-    create_dir_all(DOWNLOAD_DIR)?;
-    if !check_if_binaries_exist(&binaries, &target_triple)? {
-        let (yt_dlp_url, ffmpeg_url) = match os {
-            "linux" => (LINUX_URL, FFMPEG_LINUX_URL),
-            "windows" => (WINDOWS_URL, FFMPEG_WIN_URL),
-            _ => panic!("Unsupported operating system"),
-        };
+    let manifest_content = fs::read_to_string(MANIFEST)?;
+    let manifest: HashMap<String, Artifact> = toml::from_str(&manifest_content).expect("Invalid binaries.toml");
+    check_supported(&manifest, &target_triple);
 
+    create_dir_all(DOWNLOAD_DIR)?;
+    let up_to_date = fs::read_to_string(PROVISIONED).is_ok_and(|p| p == manifest_content);
+    if !up_to_date || !check_if_binaries_exist(&binaries, &target_triple)? {
+        let yt_dlp = fetch(&manifest, "yt-dlp", &target_triple)?;
         let yt_dlp_path = PathBuf::from(DOWNLOAD_DIR).join("yt-dlp");
-        download_and_save(yt_dlp_url, &yt_dlp_path)?;
+        fs::write(&yt_dlp_path, yt_dlp)?;
         rename_with_target_triple(&yt_dlp_path, &target_triple, os)?;
 
-        let ffmpeg_path = PathBuf::from(DOWNLOAD_DIR).join("ffmpeg");
-        download_and_save(ffmpeg_url, &ffmpeg_path)?;
+        let ffmpeg = fetch(&manifest, "ffmpeg", &target_triple)?;
+        let ffmpeg_path = PathBuf::from(DOWNLOAD_DIR).join("ffmpeg-archive");
+        fs::write(&ffmpeg_path, ffmpeg)?;
         extract_and_rename_ffmpeg_binaries(&ffmpeg_path, &target_triple, os)?;
+        fs::remove_file(&ffmpeg_path)?;
+
+        fs::write(PROVISIONED, manifest_content)?;
     }
 
     tauri_build::build();
     Ok(())
 }
 
+/// Fails early for targets the manifest has no binaries for, like macOS.
+fn check_supported(manifest: &HashMap<String, Artifact>, target_triple: &str) {
+    for (name, artifact) in manifest {
+        if !artifact.targets.contains_key(target_triple) {
+            let mut supported: Vec<&str> = artifact.targets.keys().map(String::as_str).collect();
+            supported.sort();
+            panic!(
+                "{name} has no build for {target_triple} in {MANIFEST}, supported targets are {}",
+                supported.join(", ")
+            );
+        }
+    }
+}
+
+/// Reads a pinned binary from the offline directory or downloads it, then checks its hash.
+fn fetch(manifest: &HashMap<String, Artifact>, name: &str, target_triple: &str) -> io::Result<Vec<u8>> {
+    let artifact = manifest
+        .get(name)
+        .unwrap_or_else(|| panic!("{name} is missing from {MANIFEST}"));
+    let source = artifact
+        .targets
+        .get(target_triple)
+        .unwrap_or_else(|| panic!("{name} {} has no build for {target_triple}", artifact.version));
+    // Rolling tags change their assets, so their hash can't be pinned
+    if source.url.contains("/releases/download/latest/") {
+        panic!("{name} for {target_triple} must be downloaded from an immutable release, not {}", source.url);
+    }
+    let file_name = source.url.rsplit('/').next().unwrap();
+
+    let content = match env::var_os(BINARIES_DIR_ENV) {
+        Some(dir) => {
+            let path = Path::new(&dir).join(file_name);
+            println!("cargo:rerun-if-changed={}", path.display());
+            fs::read(&path).unwrap_or_else(|e| panic!("Failed to read {}: {e}", path.display()))
+        }
+        None => download(&source.url),
+    };
+
+    let hash = sha256_hex(&content);
+    if source.sha256.is_empty() {
+        panic!(
+            "{name} {} for {target_triple} is not pinned, add sha256 = \"{hash}\" to {MANIFEST} after checking the file",
+            artifact.version
+        );
+    } else if !hash.eq_ignore_ascii_case(&source.sha256) {
+        panic!(
+            "Checksum mismatch for {file_name}: expected {}, got {hash}",
+            source.sha256
+        );
+    }
+    Ok(content)
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
 fn check_if_binaries_exist(binaries: &[&str], target_triple: &str) -> io::Result<bool> {
     let binaries_triple: Vec<String> = binaries
         .iter()
@@ -62,7 +144,19 @@ fn check_if_binaries_exist(binaries: &[&str], target_triple: &str) -> io::Result
     Ok(all_exist)
 }
 
- fn extract_and_rename_ffmpeg_binaries(path: &PathBuf, target_triple: &str, os: &str) -> io::Result<()> {
+/// Removes directories left from unpacking an earlier version, their `bin` could be picked up instead.
+fn remove_extracted_dirs() -> io::Result<()> {
+    for entry in fs::read_dir(DOWNLOAD_DIR)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            fs::remove_dir_all(path)?;
+        }
+    }
+    Ok(())
+}
+
+fn extract_and_rename_ffmpeg_binaries(path: &PathBuf, target_triple: &str, os: &str) -> io::Result<()> {
+    remove_extracted_dirs()?;
     let file = File::open(path)?;
     if os == "linux" {
         let xz_decoder = XzDecoder::new(file);
@@ -70,6 +164,8 @@ fn check_if_binaries_exist(binaries: &[&str], target_triple: &str) -> io::Result
         archive.unpack(DOWNLOAD_DIR)?;
     } else if os == "windows" {
         zip::ZipArchive::new(file)?.extract(DOWNLOAD_DIR)?;
+    } else {
+        panic!("Unpacking ffmpeg is not supported on {os}");
     }
     let mut bin_path = None;
     for entry in fs::read_dir(DOWNLOAD_DIR)? {
@@ -89,19 +185,18 @@ fn check_if_binaries_exist(binaries: &[&str], target_triple: &str) -> io::Result
             rename_with_target_triple(&target_path, target_triple, os)?;
         }
     } else {
-        eprintln!("Could not find the 'bin' directory in the extracted archive.");
+        panic!("Could not find the 'bin' directory in the extracted archive");
     }
-    Ok(())
+    remove_extracted_dirs()
 }
 
-fn download_and_save(url: &str, dest_path: &Path) -> io::Result<()> {
-    println!("Downloading {} to {}...", url, dest_path.display());
-    let response = reqwest::blocking::get(url).expect("Failed to make request");
-    let mut file = File::create(dest_path)?;
-    let content = response.bytes().expect("Failed to read response bytes");
-    file.write_all(&content)?;
-
-    Ok(())
+fn download(url: &str) -> Vec<u8> {
+    println!("Downloading {url}...");
+    reqwest::blocking::get(url)
+        .and_then(|r| r.error_for_status())
+        .and_then(|r| r.bytes())
+        .unwrap_or_else(|e| panic!("Failed to download {url}: {e}, set {BINARIES_DIR_ENV} to build offline"))
+        .to_vec()
 }
 
 fn get_target_triple() -> String {
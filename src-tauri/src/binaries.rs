@@ -0,0 +1,122 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_shell::ShellExt;
+use yaydl_shared::{BinarySettings, BinarySource, SettingsError, Tool, YaydlError};
+
+use crate::{settings, ytdlp_update, AppData};
+
+fn executable_name(tool: Tool) -> String {
+    if cfg!(target_os = "windows") {
+        format!("{}.exe", tool.name())
+    } else {
+        tool.name().to_string()
+    }
+}
+
+/// Directory the bundled sidecars are installed to.
+pub fn sidecar_dir() -> PathBuf {
+    env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        .unwrap_or_default()
+}
+
+fn find_in_path(name: &str) -> Option<PathBuf> {
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Configured source of a tool.
+pub fn source(binaries: &BinarySettings, tool: Tool) -> &BinarySource {
+    match tool {
+        Tool::YtDlp => &binaries.yt_dlp,
+        Tool::Ffmpeg | Tool::Ffprobe | Tool::Ffplay => &binaries.ffmpeg,
+    }
+}
+
+/// Path of the binary a source points to.
+pub fn resolve<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    tool: Tool,
+    source: &BinarySource,
+) -> Result<PathBuf, SettingsError> {
+    let path = match source {
+        BinarySource::Bundled => {
+            let managed = ytdlp_update::managed_binary(app_handle);
+            if tool == Tool::YtDlp && managed.exists() {
+                Some(managed)
+            } else {
+                Some(sidecar_dir().join(executable_name(tool)))
+            }
+        }
+        BinarySource::System => find_in_path(&executable_name(tool)),
+        BinarySource::Custom(path) if matches!(tool, Tool::Ffprobe | Tool::Ffplay) => {
+            Some(path.with_file_name(executable_name(tool))).filter(|p| p.is_file())
+        }
+        BinarySource::Custom(path) => Some(path.clone()).filter(|p| p.is_file()),
+    };
+    path.ok_or_else(|| SettingsError::BinaryNotFound(tool.name().into()))
+}
+
+/// Runs the binary and reads its version, failing if it does not start.
+pub async fn version<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    tool: Tool,
+    path: &Path,
+) -> Result<String, SettingsError> {
+    let invalid = || SettingsError::InvalidBinary(path.display().to_string());
+    let flag = match tool {
+        Tool::YtDlp => "--version",
+        Tool::Ffmpeg | Tool::Ffprobe | Tool::Ffplay => "-version",
+    };
+    let output = app_handle
+        .shell()
+        .command(path)
+        .arg(flag)
+        .output()
+        .await
+        .map_err(|_| invalid())?;
+    if !output.status.success() {
+        return Err(invalid());
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let first_line = stdout.lines().next().ok_or_else(invalid)?;
+    // ffmpeg prints "ffmpeg version 6.1.1 Copyright ..."
+    let version = match tool {
+        Tool::YtDlp => Some(first_line.trim()),
+        Tool::Ffmpeg | Tool::Ffprobe | Tool::Ffplay => first_line.split_whitespace().nth(2),
+    };
+    version.map(str::to_string).ok_or_else(invalid)
+}
+
+/// Version of the configured binary for `tool`.
+#[tauri::command]
+pub async fn binary_version<R: Runtime>(
+    tool: Tool,
+    app_handle: AppHandle<R>,
+    state: tauri::State<'_, Mutex<AppData>>,
+) -> Result<String, YaydlError> {
+    let binaries = state.lock().unwrap().settings.binaries.clone();
+    let path = resolve(&app_handle, tool, source(&binaries, tool))?;
+    Ok(version(&app_handle, tool, &path).await?)
+}
+
+#[tauri::command]
+pub async fn set_binary_settings<R: Runtime>(
+    value: BinarySettings,
+    app_handle: AppHandle<R>,
+    state: tauri::State<'_, Mutex<AppData>>,
+) -> Result<bool, YaydlError> {
+    for (tool, source) in [(Tool::YtDlp, &value.yt_dlp), (Tool::Ffmpeg, &value.ffmpeg)] {
+        let path = resolve(&app_handle, tool, source)?;
+        version(&app_handle, tool, &path).await?;
+    }
+    state.lock().unwrap().settings.binaries = value;
+    Ok(settings::update_settings(&app_handle, &state))
+}
@@ -0,0 +1,109 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_shell::ShellExt;
+use yaydl_shared::{Chapter, MetadataError, Tool, YaydlError};
+
+use crate::{binaries, ytdlp, AppData};
+
+pub fn chapter_args(output_dir: &str) -> Vec<String> {
+    vec![
+        "--split-chapters".into(),
+        "-o".into(),
+        format!("chapter:{output_dir}/%(title)s/%(section_number)02d - %(section_title)s.%(ext)s"),
+    ]
+}
+
+/// Track written by `--split-chapters`, e.g. `[SplitChapters] Chapter 001; Destination: ...`.
+pub fn split_destination(line: &str) -> Option<PathBuf> {
+    line.trim_end()
+        .strip_prefix("[SplitChapters]")?
+        .split_once("Destination: ")
+        .map(|(_, path)| PathBuf::from(path))
+}
+
+/// Tags every track with its chapter title, track number and the video title as album.
+pub async fn tag_tracks<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    tracks: &[PathBuf],
+    chapters: &[Chapter],
+    album: &str,
+) -> Result<(), YaydlError> {
+    let source = app_handle
+        .state::<Mutex<AppData>>()
+        .lock()
+        .unwrap()
+        .settings
+        .binaries
+        .ffmpeg
+        .clone();
+    let ffmpeg = binaries::resolve(app_handle, Tool::Ffmpeg, &source)?;
+
+    for (index, track) in tracks.iter().enumerate() {
+        let title = chapters
+            .get(index)
+            .map(|c| c.title.clone())
+            .unwrap_or_else(|| file_stem(track));
+        let file_name = track.file_name().unwrap().to_string_lossy();
+        let tagged = track.with_file_name(format!(".tagging-{file_name}"));
+        let output = app_handle
+            .shell()
+            .command(&ffmpeg)
+            .args(["-y", "-loglevel", "error", "-i"])
+            .arg(track)
+            .args(["-map", "0", "-c", "copy", "-metadata"])
+            .arg(format!("title={title}"))
+            .arg("-metadata")
+            .arg(format!("track={}/{}", index + 1, tracks.len()))
+            .arg("-metadata")
+            .arg(format!("album={album}"))
+            .arg(&tagged)
+            .output()
+            .await
+            .map_err(|e| YaydlError::TauriShellError(e.to_string()))?;
+        if !output.status.success() {
+            let _ = fs::remove_file(&tagged);
+            return Err(YaydlError::TaggingFailed);
+        }
+        fs::rename(&tagged, track).map_err(|_| YaydlError::TaggingFailed)?;
+    }
+    Ok(())
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub async fn retreive_chapters<R: Runtime>(url: &str, app_handle: AppHandle<R>) -> Result<Vec<Chapter>, YaydlError> {
+    let output = ytdlp::yt_dlp(&app_handle)?
+        .args(["--skip-download", "--print", "%(chapters)j", url])
+        .output()
+        .await
+        .map_err(|e| YaydlError::TauriShellError(e.to_string()))?;
+    if !output.status.success() {
+        return Err(MetadataError::RetreivalFailed.into());
+    }
+
+    // `null` for videos without chapters
+    let chapters: Vec<Chapter> = serde_json::from_slice::<Option<Vec<Chapter>>>(&output.stdout)
+        .map_err(|_| MetadataError::ParsingFailed)?
+        .unwrap_or_default();
+    if let Some(download) = app_handle
+        .state::<Mutex<AppData>>()
+        .lock()
+        .unwrap()
+        .download_list
+        .iter_mut()
+        .find(|d| d.metadata.url == url)
+    {
+        download.metadata.chapters = chapters.clone();
+    }
+    Ok(chapters)
+}
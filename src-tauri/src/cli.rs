@@ -0,0 +1,88 @@
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use yaydl_shared::{AddUrlArgs, LaunchArgs};
+
+use crate::deep_link::{parse_deep_link, SCHEME};
+use crate::AppData;
+
+/// Parses launch arguments, the executable path has to be skipped by the caller.
+/// Unknown flags are ignored, e.g. the `-psn_...` argument macOS passes.
+pub fn parse_launch_args<I: IntoIterator<Item = String>>(args: I) -> LaunchArgs {
+    let mut launch_args = LaunchArgs::default();
+    for arg in args {
+        if arg == "--start" {
+            launch_args.start = true;
+        } else if arg.starts_with(&format!("{SCHEME}://")) {
+            match parse_deep_link(&arg) {
+                Ok(link) => launch_args.links.push(link),
+                Err(e) => launch_args.errors.push(e.to_string()),
+            }
+        } else if !arg.starts_with('-') {
+            launch_args.links.push(AddUrlArgs {
+                url: arg,
+                ..Default::default()
+            });
+        }
+    }
+    launch_args
+}
+
+/// Hands arguments received by the already running instance to the frontend.
+pub fn forward_launch_args<R: Runtime>(app_handle: &AppHandle<R>, launch_args: LaunchArgs) {
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    if launch_args != LaunchArgs::default() {
+        let _ = app_handle.emit("launch-args", launch_args);
+    }
+}
+
+#[tauri::command]
+pub fn take_launch_args(state: tauri::State<'_, Mutex<AppData>>) -> LaunchArgs {
+    std::mem::take(&mut state.lock().unwrap().launch_args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> LaunchArgs {
+        parse_launch_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn no_arguments() {
+        assert_eq!(parse(&[]), LaunchArgs::default());
+    }
+
+    #[test]
+    fn flags_mixed_with_urls() {
+        let args = parse(&[
+            "https://www.youtube.com/watch?v=a",
+            "--start",
+            "--unknown",
+            "-psn_0_12345",
+            "https://www.youtube.com/watch?v=b",
+        ]);
+        assert!(args.start);
+        let urls: Vec<&str> = args.links.iter().map(|link| link.url.as_str()).collect();
+        assert_eq!(urls, ["https://www.youtube.com/watch?v=a", "https://www.youtube.com/watch?v=b"]);
+        assert!(args.errors.is_empty());
+    }
+
+    #[test]
+    fn deep_links() {
+        let args = parse(&[
+            "yaydl://add?url=https%3A%2F%2Fwww.youtube.com%2Fwatch%3Fv%3Da&format=mp3",
+            "yaydl://play?url=x",
+        ]);
+        assert_eq!(args.links.len(), 1);
+        assert_eq!(args.links[0].url, "https://www.youtube.com/watch?v=a");
+        assert_eq!(args.links[0].options.output_format.as_deref(), Some("mp3"));
+        assert_eq!(args.errors, ["Unknown yaydl link action: play"]);
+        assert!(!args.start);
+    }
+}
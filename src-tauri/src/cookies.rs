@@ -0,0 +1,174 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    sync::Mutex,
+};
+
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_dialog::DialogExt;
+use yaydl_shared::{CookieSource, SettingsError, YaydlError, COOKIE_BROWSERS};
+
+use crate::{settings, AppData};
+
+const COOKIE_FILE: &str = "cookies.txt";
+
+/// Messages yt-dlp prints when a video is only available with an account.
+const AUTH_ERRORS: [&str; 6] = [
+    "Sign in to confirm",
+    "members-only",
+    "Join this channel",
+    "Private video",
+    "This video is private",
+    "login required",
+];
+
+pub fn cookie_file<R: Runtime>(app_handle: &AppHandle<R>) -> PathBuf {
+    app_handle.path().app_data_dir().unwrap().join(COOKIE_FILE)
+}
+
+pub fn cookie_args<R: Runtime>(app_handle: &AppHandle<R>, source: &CookieSource) -> Vec<String> {
+    match source {
+        CookieSource::None => Vec::new(),
+        CookieSource::File => vec![
+            "--cookies".into(),
+            cookie_file(app_handle).display().to_string(),
+        ],
+        CookieSource::Browser { browser, profile } => {
+            let browser = match profile {
+                Some(profile) => format!("{browser}:{profile}"),
+                None => browser.clone(),
+            };
+            vec!["--cookies-from-browser".into(), browser]
+        }
+    }
+}
+
+/// Only `ERROR:` lines count, `--verbose` also echoes the command line including `--cookies`.
+pub fn is_auth_error(stderr: &str) -> bool {
+    stderr
+        .lines()
+        .filter(|line| line.starts_with("ERROR:"))
+        .any(|line| {
+            let line = line.to_lowercase();
+            AUTH_ERRORS.iter().any(|e| line.contains(&e.to_lowercase()))
+        })
+}
+
+/// Needs at least one cookie, every line but comments has 7 tab-separated fields.
+fn is_netscape_cookie_file(content: &str) -> bool {
+    let mut cookies = content
+        .lines()
+        .map(|l| l.trim_end_matches('\r'))
+        .filter(|l| !l.trim().is_empty())
+        .filter(|l| !l.starts_with('#') || l.starts_with("#HttpOnly_"))
+        .peekable();
+    cookies.peek().is_some() && cookies.all(|l| l.split('\t').count() == 7)
+}
+
+/// Copies a cookies.txt into the app data dir, readable only by the current user.
+#[tauri::command]
+pub async fn import_cookies<R: Runtime>(
+    app_handle: AppHandle<R>,
+    state: tauri::State<'_, Mutex<AppData>>,
+) -> Result<bool, YaydlError> {
+    let Some(path) = app_handle
+        .dialog()
+        .file()
+        .add_filter("Cookies", &["txt"])
+        .blocking_pick_file()
+    else {
+        return Err(YaydlError::FileSelectionFailed);
+    };
+    let path = path.as_path().ok_or(YaydlError::FileSelectionFailed)?;
+    let content = fs::read_to_string(path).map_err(|_| SettingsError::InvalidCookieFile)?;
+    if !is_netscape_cookie_file(&content) {
+        return Err(SettingsError::InvalidCookieFile.into());
+    }
+
+    let target = cookie_file(&app_handle);
+    fs::create_dir_all(target.parent().unwrap()).map_err(|_| SettingsError::CookieImportFailed)?;
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(&target)
+        .map_err(|_| SettingsError::CookieImportFailed)?;
+    // The mode only applies to new files, an earlier import may have left a readable one
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))
+            .map_err(|_| SettingsError::CookieImportFailed)?;
+    }
+    file.write_all(content.as_bytes())
+        .map_err(|_| SettingsError::CookieImportFailed)?;
+
+    state.lock().unwrap().settings.cookies = CookieSource::File;
+    Ok(settings::update_settings(&app_handle, &state))
+}
+
+#[tauri::command]
+pub fn set_cookie_source<R: Runtime>(
+    value: CookieSource,
+    app_handle: AppHandle<R>,
+    state: tauri::State<'_, Mutex<AppData>>,
+) -> Result<bool, YaydlError> {
+    match &value {
+        CookieSource::Browser { browser, .. } if !COOKIE_BROWSERS.contains(&browser.as_str()) => {
+            return Err(SettingsError::UnsupportedBrowser(browser.clone()).into());
+        }
+        CookieSource::File if !cookie_file(&app_handle).exists() => {
+            return Err(SettingsError::InvalidCookieFile.into());
+        }
+        CookieSource::None => {
+            let _ = fs::remove_file(cookie_file(&app_handle));
+        }
+        _ => {}
+    }
+    state.lock().unwrap().settings.cookies = value;
+    Ok(settings::update_settings(&app_handle, &state))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COOKIE: &str = ".youtube.com\tTRUE\t/\tTRUE\t1767225600\tSID\tvalue";
+
+    #[test]
+    fn accepts_cookie_files() {
+        assert!(is_netscape_cookie_file(&format!(
+            "# Netscape HTTP Cookie File\n\n{COOKIE}\n"
+        )));
+        assert!(is_netscape_cookie_file(&format!("#HttpOnly_{COOKIE}\r\n")));
+        assert!(is_netscape_cookie_file(
+            ".youtube.com\tTRUE\t/\tTRUE\t0\tEMPTY\t\n"
+        ));
+    }
+
+    #[test]
+    fn rejects_files_without_cookies() {
+        assert!(!is_netscape_cookie_file(""));
+        assert!(!is_netscape_cookie_file(
+            "# Netscape HTTP Cookie File\n# comment\n"
+        ));
+        assert!(!is_netscape_cookie_file(&format!(
+            "# Netscape HTTP Cookie File\n{COOKIE}\nnot a cookie\n"
+        )));
+        assert!(!is_netscape_cookie_file("{\"cookies\": []}"));
+    }
+
+    #[test]
+    fn only_error_lines_are_auth_errors() {
+        assert!(is_auth_error("[youtube] abc: Downloading webpage\nERROR: [youtube] abc: Sign in to confirm your age\n"));
+        assert!(!is_auth_error("[debug] Command-line config: ['--cookies', 'cookies.txt']\nERROR: Unable to download\n"));
+        assert!(!is_auth_error(
+            "WARNING: Private video in playlist skipped\n"
+        ));
+    }
+}
@@ -0,0 +1,87 @@
+use tauri::{AppHandle, Runtime};
+use url::Url;
+use yaydl_shared::{AddUrlArgs, DeepLinkError, DownloadOptions, AUDIO_FORMATS};
+
+pub const SCHEME: &str = "yaydl";
+
+/// Parses a link of the form `yaydl://add?url=<video url>&format=<audio format>`.
+pub fn parse_deep_link(link: &str) -> Result<AddUrlArgs, DeepLinkError> {
+    let link = Url::parse(link).map_err(|_| DeepLinkError::Malformed)?;
+    if link.scheme() != SCHEME {
+        return Err(DeepLinkError::Malformed);
+    }
+    match link.host_str() {
+        Some("add") => {}
+        Some(action) => return Err(DeepLinkError::UnknownAction(action.to_string())),
+        None => return Err(DeepLinkError::Malformed),
+    }
+
+    let mut url = None;
+    let mut options = DownloadOptions::default();
+    for (key, value) in link.query_pairs() {
+        match key.as_ref() {
+            "url" => url = Some(value.into_owned()),
+            "format" => {
+                if !AUDIO_FORMATS.contains(&value.as_ref()) {
+                    return Err(DeepLinkError::UnsupportedFormat(value.into_owned()));
+                }
+                options.output_format = Some(value.into_owned());
+            }
+            _ => {}
+        }
+    }
+    let url = url.filter(|url| !url.is_empty()).ok_or(DeepLinkError::MissingUrl)?;
+
+    Ok(AddUrlArgs { url, options })
+}
+
+/// Registers the scheme handler for the running executable. Installed packages
+/// get the handler from the desktop entry, this covers AppImages and dev builds.
+#[cfg(target_os = "linux")]
+pub fn register_scheme<R: Runtime>(app_handle: &AppHandle<R>) {
+    use tauri_plugin_deep_link::DeepLinkExt;
+
+    if let Err(e) = app_handle.deep_link().register_all() {
+        println!("{}", e);
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn register_scheme<R: Runtime>(_app_handle: &AppHandle<R>) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_add_links() {
+        let args = parse_deep_link("yaydl://add?url=https://www.youtube.com/watch?v=abc&format=flac").unwrap();
+        assert_eq!(args.url, "https://www.youtube.com/watch?v=abc");
+        assert_eq!(args.options.output_format.as_deref(), Some("flac"));
+    }
+
+    #[test]
+    fn decodes_percent_encoded_urls() {
+        let args =
+            parse_deep_link("yaydl://add?url=https%3A%2F%2Fwww.youtube.com%2Fwatch%3Fv%3Dabc%26t%3D90&extra=1").unwrap();
+        assert_eq!(args.url, "https://www.youtube.com/watch?v=abc&t=90");
+        assert_eq!(args.options, DownloadOptions::default());
+    }
+
+    #[test]
+    fn rejects_invalid_links() {
+        assert!(matches!(parse_deep_link("https://add?url=x"), Err(DeepLinkError::Malformed)));
+        assert!(matches!(parse_deep_link("yaydl:add?url=x"), Err(DeepLinkError::Malformed)));
+        assert!(matches!(parse_deep_link("not a link"), Err(DeepLinkError::Malformed)));
+        assert!(matches!(
+            parse_deep_link("yaydl://remove?url=x"),
+            Err(DeepLinkError::UnknownAction(action)) if action == "remove"
+        ));
+        assert!(matches!(parse_deep_link("yaydl://add"), Err(DeepLinkError::MissingUrl)));
+        assert!(matches!(parse_deep_link("yaydl://add?url="), Err(DeepLinkError::MissingUrl)));
+        assert!(matches!(
+            parse_deep_link("yaydl://add?url=x&format=exe"),
+            Err(DeepLinkError::UnsupportedFormat(format)) if format == "exe"
+        ));
+    }
+}
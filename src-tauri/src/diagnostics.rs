@@ -0,0 +1,52 @@
+use std::{fs, path::Path, sync::Mutex};
+
+use tauri::{AppHandle, Manager, Runtime};
+use yaydl_shared::{BinaryCheck, Diagnostics, Tool};
+
+use crate::{binaries, AppData};
+
+fn is_writable(dir: &Path) -> bool {
+    let probe = dir.join(".yaydl-write-test");
+    let writable = fs::write(&probe, b"").is_ok();
+    let _ = fs::remove_file(probe);
+    writable
+}
+
+/// Checks that the configured binaries run and the output dir is writable.
+#[tauri::command]
+pub async fn diagnostics<R: Runtime>(app_handle: AppHandle<R>) -> Diagnostics {
+    let settings = app_handle
+        .state::<Mutex<AppData>>()
+        .lock()
+        .unwrap()
+        .settings
+        .clone();
+
+    let mut checks = Vec::new();
+    for tool in [Tool::YtDlp, Tool::Ffmpeg, Tool::Ffprobe, Tool::Ffplay] {
+        let path = binaries::resolve(&app_handle, tool, binaries::source(&settings.binaries, tool));
+        let version = match &path {
+            Ok(path) => binaries::version(&app_handle, tool, path)
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        checks.push(BinaryCheck {
+            tool,
+            path: path.ok(),
+            version,
+        });
+    }
+
+    let config_dir = app_handle.path().app_config_dir().unwrap();
+    Diagnostics {
+        app_version: app_handle.package_info().version.to_string(),
+        os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+        binaries: checks,
+        output_dir_writable: is_writable(&settings.output_dir),
+        output_dir: settings.output_dir,
+        settings_file: config_dir.join("settings.toml"),
+        config_dir,
+        data_dir: app_handle.path().app_data_dir().unwrap(),
+    }
+}
@@ -1,13 +1,43 @@
-use std::sync::Mutex;
-use tauri::{AppHandle, Emitter, Manager, Runtime};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tauri::{AppHandle, Emitter, Manager, Runtime, WindowEvent};
 use tauri_plugin_clipboard_manager::ClipboardExt;
-use tauri_plugin_shell::ShellExt;
+use tauri_plugin_deep_link::DeepLinkExt;
+use tauri_plugin_shell::{
+    process::{CommandChild, CommandEvent},
+    ShellExt,
+};
 
+mod binaries;
+mod chapters;
+mod cli;
+mod cookies;
+mod deep_link;
+mod diagnostics;
+mod library;
+mod loudness;
+mod notifications;
+mod playback;
+mod post_process;
+mod queue_file;
+mod schedule;
 mod settings;
+mod sponsorblock;
+mod subscriptions;
+mod subtitles;
+mod time_range;
+mod tray;
+mod verify;
+mod video;
+mod ytdlp;
+mod ytdlp_update;
 use settings::Setup;
-use tauri_plugin_updater::UpdaterExt;
 use yaydl_shared::{
-    AddLinkError, Download, DownloadEvent, DownloadState, Metadata, MetadataError, Settings, UpdateError, YaydlError
+    AddLinkError, Download, DownloadEvent, DownloadOptions, DownloadState, LaunchArgs, LoudnessMode, Metadata, MetadataError, PostProcessStep, Settings, SettingsError, Subscription, UpdateError, YaydlError, AUDIO_FORMATS, is_video_format, parse_timestamp
 };
 
 type Result<T> = std::result::Result<T, YaydlError>;
@@ -15,6 +45,12 @@ type Result<T> = std::result::Result<T, YaydlError>;
 pub struct AppData {
     download_list: Vec<Download>,
     settings: Settings,
+    launch_args: LaunchArgs,
+    running: HashMap<String, CommandChild>,
+    scheduled: HashSet<String>,
+    subscriptions: Vec<Subscription>,
+    player: Option<playback::Player>,
+    library: library::Library,
 }
 
 impl Default for AppData {
@@ -22,6 +58,12 @@ impl Default for AppData {
         Self {
             download_list: Default::default(),
             settings: Settings::with_defaults(),
+            launch_args: Default::default(),
+            running: Default::default(),
+            scheduled: Default::default(),
+            subscriptions: Default::default(),
+            player: None,
+            library: Default::default(),
         }
     }
 }
@@ -43,6 +85,102 @@ async fn clear_downloads<R: Runtime>(app_handle: tauri::AppHandle<R>) {
         .lock()
         .unwrap()
         .download_list.clear();
+    tray::update_tray(&app_handle);
+}
+
+/// Removes downloads, stopping those that are running.
+#[tauri::command]
+async fn remove_downloads<R: Runtime>(app_handle: tauri::AppHandle<R>, ids: Vec<String>) -> Vec<Download> {
+    let (downloads, children) = {
+        let state = app_handle.state::<Mutex<AppData>>();
+        let mut state = state.lock().unwrap();
+        state.download_list.retain(|d| !ids.contains(&d.metadata.id));
+        let children: Vec<CommandChild> = ids
+            .iter()
+            .filter_map(|id| {
+                state.scheduled.remove(id);
+                state.running.remove(id)
+            })
+            .collect();
+        (state.download_list.clone(), children)
+    };
+    for child in children {
+        let _ = child.kill();
+    }
+    tray::update_tray(&app_handle);
+    downloads
+}
+
+/// Sets the output format of downloads, `None` for the default format.
+#[tauri::command]
+async fn set_downloads_format<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    ids: Vec<String>,
+    format: Option<String>,
+) -> Vec<Download> {
+    let state = app_handle.state::<Mutex<AppData>>();
+    let mut state = state.lock().unwrap();
+    for download in state.download_list.iter_mut().filter(|d| ids.contains(&d.metadata.id)) {
+        download.options.output_format = format.clone();
+    }
+    state.download_list.clone()
+}
+
+/// Makes downloads idle again so they can be retried.
+#[tauri::command]
+async fn reset_downloads<R: Runtime>(app_handle: tauri::AppHandle<R>, ids: Vec<String>) -> Vec<Download> {
+    let downloads = {
+        let state = app_handle.state::<Mutex<AppData>>();
+        let mut state = state.lock().unwrap();
+        for download in state.download_list.iter_mut().filter(|d| ids.contains(&d.metadata.id)) {
+            download.download_state = DownloadState::Idle;
+        }
+        state.download_list.clone()
+    };
+    tray::update_tray(&app_handle);
+    downloads
+}
+
+/// Copies the URLs of downloads in queue order, one per line.
+#[tauri::command]
+fn copy_urls<R: Runtime>(app_handle: tauri::AppHandle<R>, ids: Vec<String>) -> Result<usize> {
+    let urls: Vec<String> = app_handle
+        .state::<Mutex<AppData>>()
+        .lock()
+        .unwrap()
+        .download_list
+        .iter()
+        .filter(|d| ids.contains(&d.metadata.id))
+        .map(|d| d.metadata.url.clone())
+        .collect();
+    app_handle
+        .clipboard()
+        .write_text(urls.join("\n"))
+        .map_err(|_| YaydlError::ClipboardWriteFailed)?;
+    Ok(urls.len())
+}
+
+/// Moves a download to `index`, clamped to the end of the queue.
+#[tauri::command]
+async fn move_download<R: Runtime>(app_handle: tauri::AppHandle<R>, id: String, index: usize) -> Vec<Download> {
+    let state = app_handle.state::<Mutex<AppData>>();
+    let mut state = state.lock().unwrap();
+    if let Some(from) = state.download_list.iter().position(|d| d.metadata.id == id) {
+        let download = state.download_list.remove(from);
+        let index = index.min(state.download_list.len());
+        state.download_list.insert(index, download);
+    }
+    state.download_list.clone()
+}
+
+#[tauri::command]
+async fn set_download_priority<R: Runtime>(app_handle: tauri::AppHandle<R>, id: String, high: bool) -> Vec<Download> {
+    let state = app_handle.state::<Mutex<AppData>>();
+    let mut state = state.lock().unwrap();
+    if let Some(download) = state.download_list.iter_mut().find(|d| d.metadata.id == id) {
+        download.high_priority = high;
+    }
+    state.download_list.clone()
 }
 
 #[tauri::command]
@@ -54,6 +192,125 @@ async fn update_download<R: Runtime>(app_handle: tauri::AppHandle<R>, id: String
         .download_list.iter_mut().find(|d| d.metadata.id == id) {
         download.download_state = state;
     }
+    tray::update_tray(&app_handle);
+}
+
+/// Whether a download was stopped by removing it rather than by `stop_all`.
+pub fn cancelled(state: &AppData, id: &str) -> YaydlError {
+    if state.download_list.iter().any(|d| d.metadata.id == id) {
+        YaydlError::DownloadCancelled
+    } else {
+        YaydlError::DownloadRemoved
+    }
+}
+
+fn stop_downloads<R: Runtime>(app_handle: &AppHandle<R>) {
+    let state = app_handle.state::<Mutex<AppData>>();
+    let (running, waiting) = {
+        let mut state = state.lock().unwrap();
+        state.scheduled.clear();
+        let waiting: Vec<String> = state
+            .download_list
+            .iter()
+            .filter(|d| d.download_state == DownloadState::Scheduled)
+            .map(|d| d.metadata.id.clone())
+            .collect();
+        (std::mem::take(&mut state.running), waiting)
+    };
+    for (_, child) in running {
+        let _ = child.kill();
+    }
+    for id in waiting {
+        schedule::set_state(app_handle, &id, DownloadState::Idle);
+    }
+}
+
+#[tauri::command]
+fn stop_all<R: Runtime>(app_handle: AppHandle<R>) {
+    stop_downloads(&app_handle);
+}
+
+#[tauri::command]
+fn quit_app<R: Runtime>(app_handle: AppHandle<R>) {
+    app_handle.exit(0);
+}
+
+/// yt-dlp prints `NA` for missing fields.
+pub fn printed(field: &str) -> String {
+    let field = field.trim();
+    if field == "NA" {
+        String::new()
+    } else {
+        field.to_string()
+    }
+}
+
+/// Seconds since the Unix epoch, stored as the time a download was added.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+pub fn is_valid_link(url: &str) -> bool {
+    url.contains("https://www.youtube.com/")
+}
+
+pub fn validate_options(options: &DownloadOptions) -> Result<()> {
+    time_range::validate(&options.time_ranges)?;
+    if let Some(format) = options
+        .output_format
+        .as_ref()
+        .filter(|f| !AUDIO_FORMATS.contains(&f.as_str()) && !is_video_format(f))
+    {
+        return Err(SettingsError::UnsupportedFormat(format.clone()).into());
+    }
+    if let Some(sponsorblock) = &options.sponsorblock {
+        sponsorblock::validate(sponsorblock)?;
+    }
+    if let Some(subtitles) = &options.subtitles {
+        subtitles::validate(subtitles)?;
+    }
+    Ok(())
+}
+
+/// Checks a new download and inserts it at `index` of the queue, returns its URL.
+/// Commands find downloads by ID, so a known ID counts as already added even for another URL.
+pub fn enqueue(state: &mut AppData, mut download: Download, index: usize) -> Result<String> {
+    let url = download.metadata.url.trim().to_string();
+    if !is_valid_link(&url) {
+        return Err(YaydlError::AddLinkError(AddLinkError::NoValidLink));
+    }
+    let id = &download.metadata.id;
+    if state
+        .download_list
+        .iter()
+        .any(|d| d.metadata.url == url || (!id.is_empty() && d.metadata.id == *id))
+    {
+        return Err(YaydlError::AddLinkError(AddLinkError::AlreadyAdded));
+    }
+    validate_options(&download.options)?;
+    if download.options.time_ranges.is_empty() {
+        download.options.time_ranges.extend(time_range::from_url(&url));
+    }
+    download.metadata.url = url.clone();
+    download.added = now();
+    state.download_list.insert(index, download);
+    Ok(url)
+}
+
+fn add_link(state: &Mutex<AppData>, url: String, options: DownloadOptions) -> Result<(String, Vec<Download>)> {
+    let download = Download {
+        metadata: Metadata {
+            url,
+            ..Default::default()
+        },
+        options,
+        ..Default::default()
+    };
+    let mut state = state.lock().unwrap();
+    let url = enqueue(&mut state, download, 0)?;
+    Ok((url, state.download_list.clone()))
 }
 
 #[tauri::command]
@@ -61,43 +318,100 @@ async fn try_add<R: Runtime>(app_handle: AppHandle<R>) -> Result<(String, Vec<Do
     let content = app_handle.clipboard().read_text();
     let state = app_handle.state::<Mutex<AppData>>();
     match content {
-        Ok(url) if url.contains("https://www.youtube.com/") => {
-            let download = Download {
-                metadata: Metadata {
-                    url: url.clone(),
-                    ..Default::default()
-                },
-                ..Default::default()
-            };
-            let mut state = state.lock().unwrap();
-            let contains = state.download_list.contains(&download);
-            if !contains {
-                state.download_list.insert(0, download);
-                Ok((url, state.download_list.clone()))
-            } else {
-                Err(YaydlError::AddLinkError(AddLinkError::AlreadyAdded))
-            }
-        }
-        Ok(_) => Err(YaydlError::AddLinkError(AddLinkError::NoValidLink)),
+        Ok(url) => add_link(&state, url, Default::default()),
         Err(_) => Err(YaydlError::AddLinkError(AddLinkError::ClipboardRead)),
     }
 }
 
 #[tauri::command]
-fn open_explorer<R: Runtime>(
+async fn set_download_options<R: Runtime>(
+    id: String,
+    options: DownloadOptions,
     app_handle: AppHandle<R>,
-    state: tauri::State<'_, Mutex<AppData>>,
 ) -> Result<()> {
-    let shell = app_handle.shell();
-    let explorer = if cfg!(target_os = "windows") {
-        "explorer"
+    validate_options(&options)?;
+    if let Some(download) = app_handle
+        .state::<Mutex<AppData>>()
+        .lock()
+        .unwrap()
+        .download_list
+        .iter_mut()
+        .find(|d| d.metadata.id == id)
+    {
+        download.options = options;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn add_url<R: Runtime>(
+    url: String,
+    options: DownloadOptions,
+    app_handle: AppHandle<R>,
+) -> Result<(String, Vec<Download>)> {
+    add_link(&app_handle.state::<Mutex<AppData>>(), url, options)
+}
+
+fn explorer_command() -> Result<&'static str> {
+    if cfg!(target_os = "windows") {
+        Ok("explorer")
     } else if cfg!(target_os = "macos") {
-        "open"
+        Ok("open")
     } else if cfg!(target_os = "linux") {
-        "xdg-open"
+        Ok("xdg-open")
     } else {
-        return Err(YaydlError::UnsupportedOs);
+        Err(YaydlError::UnsupportedOs)
+    }
+}
+
+/// Shows `path` in the file manager, selected where the platform supports it.
+pub fn reveal<R: Runtime>(app_handle: &AppHandle<R>, path: &Path) -> Result<()> {
+    // Most Linux file managers select the file through FileManager1, xdg-open only opens the folder
+    if cfg!(target_os = "linux") && path.is_file() {
+        if let Ok(uri) = url::Url::from_file_path(path) {
+            let selected = std::process::Command::new("dbus-send")
+                .args([
+                    "--session",
+                    "--print-reply",
+                    "--dest=org.freedesktop.FileManager1",
+                    "/org/freedesktop/FileManager1",
+                    "org.freedesktop.FileManager1.ShowItems",
+                    &format!("array:string:{uri}"),
+                    "string:",
+                ])
+                .output()
+                .is_ok_and(|output| output.status.success());
+            if selected {
+                return Ok(());
+            }
+        }
+    }
+    let explorer = explorer_command()?;
+    let args = if path.is_dir() {
+        vec![path.display().to_string()]
+    } else if cfg!(target_os = "windows") {
+        vec![format!("/select,{}", path.display())]
+    } else if cfg!(target_os = "macos") {
+        vec!["-R".to_string(), path.display().to_string()]
+    } else {
+        vec![path.parent().unwrap_or(path).display().to_string()]
     };
+    app_handle
+        .shell()
+        .command(explorer)
+        .args(args)
+        .spawn()
+        .map_err(|e| YaydlError::TauriShellError(e.to_string()))?;
+    Ok(())
+}
+
+#[tauri::command]
+fn open_explorer<R: Runtime>(
+    app_handle: AppHandle<R>,
+    state: tauri::State<'_, Mutex<AppData>>,
+) -> Result<()> {
+    let shell = app_handle.shell();
+    let explorer = explorer_command()?;
     let output_dir = state
         .lock()
         .unwrap()
@@ -111,16 +425,20 @@ fn open_explorer<R: Runtime>(
 
 #[tauri::command]
 async fn retreive_metadata<R: Runtime>(url: &str, app_handle: AppHandle<R>) -> Result<Metadata> {
-    let shell = app_handle.shell();
-    let output = shell
-        .sidecar("yt-dlp")
-        .map_err(|e| YaydlError::TauriShellError(e.to_string()))?
+    let output = ytdlp::yt_dlp(&app_handle)?
         .args([
             "--verbose",
-            "--get-id",
-            "--get-title",
-            "--get-duration",
-            "--get-thumbnail",
+            // One line each, in this order
+            "--print",
+            "title",
+            "--print",
+            "id",
+            "--print",
+            "thumbnail",
+            "--print",
+            "duration_string",
+            "--print",
+            "uploader",
             url,
         ])
         .output()
@@ -128,23 +446,28 @@ async fn retreive_metadata<R: Runtime>(url: &str, app_handle: AppHandle<R>) -> R
         .map_err(|e| YaydlError::TauriShellError(e.to_string()))?;
 
     if !output.status.success() {
+        if cookies::is_auth_error(&String::from_utf8_lossy(&output.stderr)) {
+            return Err(YaydlError::AuthRequired);
+        }
         return Err(YaydlError::MetadataError(MetadataError::RetreivalFailed));
     }
 
     let output_str = std::str::from_utf8(&output.stdout).map_err(|_| YaydlError::Utf8Conversion)?;
-    let metadata: Vec<&str> = output_str.split("\n\n").collect();
+    let metadata: Vec<String> = output_str.lines().map(printed).collect();
 
-    if metadata.len() < 4 {
+    if metadata.len() < 5 {
         return Err(YaydlError::MetadataError(MetadataError::MissingFields));
     }
 
     let metadata = Metadata {
-        title: metadata[0].to_string(),
-        id: metadata[1].to_string(),
-        thumbnail: metadata[2].to_string(),
-        duration: metadata[3].to_string(),
+        title: metadata[0].clone(),
+        id: metadata[1].clone(),
+        thumbnail: metadata[2].clone(),
+        duration: metadata[3].clone(),
+        uploader: metadata[4].clone(),
         url: url.to_string(),
         loading: false,
+        ..Default::default()
     };
 
     if let Some(d) = app_handle
@@ -168,55 +491,205 @@ async fn execute_yt_dl<R: Runtime>(
     app_handle: AppHandle<R>,
     state: tauri::State<'_, Mutex<AppData>>,
 ) -> Result<()> {
-    let shell = app_handle.shell();
-    let output_dir = state
-        .lock()
-        .unwrap()
-        .settings
+    let (options, metadata, settings) = {
+        let state = state.lock().unwrap();
+        // Removed after the queue started
+        let download = state
+            .download_list
+            .iter()
+            .find(|d| d.metadata.id == id)
+            .ok_or(YaydlError::DownloadRemoved)?;
+        (download.options.clone(), download.metadata.clone(), state.settings.clone())
+    };
+    let output_dir = options
         .output_dir
+        .unwrap_or(settings.output_dir)
         .display()
         .to_string();
-    let output_format = state.lock().unwrap().settings.output_format.to_string();
+    let output_format = options.output_format.unwrap_or(settings.output_format);
+    let subtitles = options.subtitles.unwrap_or(settings.subtitles);
+    let sponsorblock = options.sponsorblock.unwrap_or(settings.sponsorblock);
+    let loudness = options.loudness.unwrap_or(settings.loudness);
+    let split_chapters = options.split_chapters && !metadata.chapters.is_empty();
+    let keep_video = is_video_format(&output_format);
 
-    let (mut rx, _) = shell
-        .sidecar("yt-dlp")
-        .map_err(|e| YaydlError::TauriShellError(e.to_string()))?
-        .args([
-            "--newline",
-            "-x",
-            "--audio-format",
-            &output_format,
-            "-o",
-            &format!("{output_dir}/%(title)s.%(ext)s"),
-            &url,
-        ])
+    let section_suffix = if options.time_ranges.is_empty() {
+        ""
+    } else {
+        time_range::SECTION_SUFFIX
+    };
+
+    schedule::wait_for_window(&app_handle, &id).await?;
+    let mut args = vec![
+        "--newline".to_string(),
+        // Artist, album artist and source URL for the library
+        "--embed-metadata".into(),
+        "-o".into(),
+        format!("{output_dir}/%(title)s{section_suffix}.%(ext)s"),
+    ];
+    if keep_video {
+        args.extend(video::video_args(&output_format));
+    } else {
+        args.extend(["-x".into(), "--audio-format".into(), output_format.clone()]);
+    }
+    args.extend(time_range::section_args(&options.time_ranges));
+    args.extend(subtitles::subtitle_args(&subtitles, &output_format));
+    args.extend(sponsorblock::sponsorblock_args(&sponsorblock));
+    if split_chapters {
+        args.extend(chapters::chapter_args(&output_dir));
+    }
+    // Read after waiting so changes made while the download was scheduled apply
+    if let Some(rate_limit) = state.lock().unwrap().settings.rate_limit.clone() {
+        args.extend(["--limit-rate".into(), rate_limit]);
+    }
+    args.push(url);
+
+    let (mut rx, child) = ytdlp::yt_dlp(&app_handle)?
+        .args(args)
         .spawn()
         .map_err(|e| YaydlError::TauriShellError(e.to_string()))?;
+    state.lock().unwrap().running.insert(id.clone(), child);
 
+    let mut outputs = Vec::new();
+    let mut tracks = Vec::new();
+    let mut stderr = String::new();
+    let mut success = false;
     while let Some(event) = rx.recv().await {
-        if let tauri_plugin_shell::process::CommandEvent::Stdout(line) = event {
-            let line = std::str::from_utf8(&line).map_err(|_| YaydlError::Utf8Conversion)?;
-            if line.starts_with("[download]") {
-                let (_, remainder) = line.split_at("[download]".len());
-                let remainder = remainder.trim_start();
-                let percent = remainder.split(' ').collect::<Vec<_>>()[0];
-                let percent = &percent[..percent.len() - 1];
-                if let Ok(progress) = percent.parse::<f32>() {
-                    let progress = progress as u8;
-                    app_handle
-                        .emit(
-                            "download-progress",
-                            DownloadEvent {
-                                id: id.clone(),
-                                progress,
-                            },
-                        )
-                        .unwrap();
+        match event {
+            CommandEvent::Terminated(payload) => success = payload.code == Some(0),
+            CommandEvent::Stderr(line) => {
+                stderr.push_str(&String::from_utf8_lossy(&line));
+                stderr.push('\n');
+            }
+            CommandEvent::Stdout(line) => {
+                let line = std::str::from_utf8(&line).map_err(|_| YaydlError::Utf8Conversion)?;
+                if keep_video {
+                    video::track_output(&mut outputs, line);
+                }
+                if let Some(path) = line.trim_end().strip_prefix("[ExtractAudio] Destination: ") {
+                    outputs.push(PathBuf::from(path));
+                } else if let Some((path, _)) = line
+                    .strip_prefix("[ExtractAudio] Not converting audio ")
+                    .and_then(|rest| rest.split_once("; file is already in target format"))
+                {
+                    outputs.push(PathBuf::from(path));
+                } else if let Some(track) = chapters::split_destination(line) {
+                    tracks.push(track);
+                } else if line.starts_with("[download]") {
+                    let (_, remainder) = line.split_at("[download]".len());
+                    let remainder = remainder.trim_start();
+                    let percent = remainder.split(' ').collect::<Vec<_>>()[0];
+                    let percent = &percent[..percent.len() - 1];
+                    if let Ok(progress) = percent.parse::<f32>() {
+                        let progress = progress as u8;
+                        app_handle
+                            .emit(
+                                "download-progress",
+                                DownloadEvent {
+                                    id: id.clone(),
+                                    progress,
+                                },
+                            )
+                            .unwrap();
+                    }
                 }
             }
+            _ => {}
         }
     }
 
+    // The child is only missing if `stop_all` or removing the download killed it
+    {
+        let mut state = state.lock().unwrap();
+        if state.running.remove(&id).is_none() {
+            return Err(cancelled(&state, &id));
+        }
+    }
+    if !success {
+        notifications::notify_download_failed(&app_handle, &id);
+        if cookies::is_auth_error(&stderr) {
+            return Err(YaydlError::AuthRequired);
+        }
+        return Err(YaydlError::DownloadFailed);
+    }
+    if keep_video {
+        outputs = video::finished(outputs, &output_format);
+    }
+    let split = split_chapters && !tracks.is_empty();
+    if split {
+        if let Err(e) = chapters::tag_tracks(&app_handle, &tracks, &metadata.chapters, &metadata.title).await {
+            notifications::notify_download_failed(&app_handle, &id);
+            return Err(e);
+        }
+        // The tracks replace the file of the whole video
+        for path in outputs {
+            let _ = std::fs::remove_file(path);
+        }
+        outputs = tracks;
+    }
+    // Removed segments make the files shorter than the video
+    let removes_segments = sponsorblock.enabled && !sponsorblock.remove.is_empty();
+    let expected = if removes_segments {
+        Vec::new()
+    } else {
+        verify::expected_durations(
+            parse_timestamp(&metadata.duration),
+            &options.time_ranges,
+            split.then_some(metadata.chapters.as_slice()),
+        )
+    };
+    if let Err(e) = verify::verify(&app_handle, &outputs, &expected).await {
+        notifications::notify_download_failed(&app_handle, &id);
+        return Err(e);
+    }
+    let measured = match loudness::process(&app_handle, &outputs, &loudness).await {
+        Ok(measured) => measured,
+        Err(e) => {
+            notifications::notify_download_failed(&app_handle, &id);
+            return Err(e);
+        }
+    };
+    // Read after downloading so the pipeline saved last is used
+    let mut steps = state.lock().unwrap().settings.post_processing.clone();
+    // A per-download loudness override already normalized or tagged the files
+    if loudness.mode != LoudnessMode::Off && post_process::has_normalize(&steps) {
+        steps.retain(|step| !matches!(step, PostProcessStep::Normalize(_)));
+    }
+    let outputs = match post_process::run(&app_handle, &id, &metadata, outputs, &steps).await {
+        Ok(outputs) => outputs,
+        Err(e) => {
+            notifications::notify_download_failed(&app_handle, &id);
+            return Err(e);
+        }
+    };
+    // The folder of the tracks when split by chapter
+    let output_path = if split {
+        outputs.first().and_then(|track| track.parent()).map(PathBuf::from)
+    } else {
+        outputs.first().cloned()
+    };
+    let subscription = {
+        let mut state = state.lock().unwrap();
+        let subscription = state
+            .download_list
+            .iter_mut()
+            .find(|d| d.metadata.id == id)
+            .and_then(|download| {
+                download.output_path = output_path;
+                download.loudness = measured;
+                download.subscription.clone()
+            });
+        // Lets the UI show the measured loudness
+        if measured.is_some() {
+            let _ = app_handle.emit("downloads-changed", state.download_list.clone());
+        }
+        subscription
+    };
+    if let Some(subscription) = subscription {
+        subscriptions::mark_downloaded(&app_handle, &subscription, &id);
+    }
+    notifications::notify_download_finished(&app_handle, &id);
+
     Ok(())
 }
 
@@ -226,8 +699,7 @@ async fn check_update<R: Runtime>(app: tauri::AppHandle<R>) -> Result<bool> {
     if std::env::var("YAYDL_SIMULATE_UPDATE").ok().as_deref() == Some("1") {
         return Ok(true);
     }
-    let update = app
-        .updater_builder()
+    let update = ytdlp::updater_builder(&app)
         .build()
         .map_err(|_| YaydlError::UpdateError(UpdateError::BuildFailed))?
         .check()
@@ -247,8 +719,7 @@ async fn start_update<R: Runtime>(app: tauri::AppHandle<R>) -> Result<()> {
         let _ = app.emit("update-finished", ());
         return Ok(());
     }
-    if let Some(update) = app
-        .updater_builder()
+    if let Some(update) = ytdlp::updater_builder(&app)
         .build()
         .map_err(|_| YaydlError::UpdateError(UpdateError::BuildFailed))?
         .check()
@@ -279,39 +750,116 @@ async fn start_update<R: Runtime>(app: tauri::AppHandle<R>) -> Result<()> {
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
+            cli::forward_launch_args(app, cli::parse_launch_args(args.into_iter().skip(1)));
+        }))
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .setup(|app| {
-            let handle = app.handle().clone();
-            tauri::async_runtime::spawn(async move {
-                if let Err(e) = check_update(handle).await {
-                    println!("{}", e);
-                }
-            });
             let config_dir = app.path().app_config_dir().unwrap();
             let app_data = AppData {
                 settings: Settings::setup_settings(&config_dir),
+                launch_args: cli::parse_launch_args(std::env::args().skip(1)),
+                subscriptions: subscriptions::load(app.handle()),
+                library: library::load(app.handle()),
                 ..Default::default()
             };
             app.manage(Mutex::new(app_data));
+            subscriptions::start_poller(app.handle().clone());
+            library::watch(app.handle());
+            // The updater reads the network settings, so check after managing the app data
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = check_update(handle).await {
+                    println!("{}", e);
+                }
+            });
+            deep_link::register_scheme(app.handle());
+            tray::create_tray(app.handle())?;
+            let handle = app.handle().clone();
+            app.deep_link().on_open_url(move |event| {
+                let urls = event.urls().iter().map(|url| url.to_string()).collect::<Vec<_>>();
+                cli::forward_launch_args(&handle, cli::parse_launch_args(urls));
+            });
             Ok(())
         })
+        .on_window_event(|window, event| {
+            if let WindowEvent::CloseRequested { api, .. } = event {
+                let state = window.state::<Mutex<AppData>>();
+                if state.lock().unwrap().settings.run_in_background {
+                    api.prevent_close();
+                    let _ = window.hide();
+                }
+            }
+        })
         .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
         .invoke_handler(tauri::generate_handler![
             execute_yt_dl,
             try_add,
+            add_url,
             retreive_metadata,
             open_explorer,
             get_downloads,
             clear_downloads,
+            remove_downloads,
+            set_downloads_format,
+            reset_downloads,
+            copy_urls,
+            move_download,
+            set_download_priority,
             update_download,
+            stop_all,
+            quit_app,
             check_update,
             start_update,
             settings::choose_output_dir,
             settings::set_output_format,
             settings::set_dark_theme,
+            settings::set_run_in_background,
+            settings::set_notification_settings,
+            settings::set_rate_limit,
+            settings::set_schedule,
+            settings::set_network_settings,
+            settings::set_subtitle_options,
+            settings::set_sponsorblock_settings,
+            settings::set_loudness_settings,
+            settings::set_post_processing,
+            settings::set_queue_sort,
+            schedule::mark_scheduled,
+            queue_file::export_queue,
+            queue_file::import_queue,
+            playback::preview_download,
+            playback::play_output,
+            playback::seek_playback,
+            playback::stop_playback,
+            playback::play_file,
+            library::get_library,
+            library::rescan_library,
+            library::reveal_file,
+            library::retag_file,
+            library::delete_file,
+            ytdlp::test_connection,
+            cookies::import_cookies,
+            cookies::set_cookie_source,
+            ytdlp_update::yt_dlp_version,
+            ytdlp_update::update_yt_dlp,
+            ytdlp_update::rollback_yt_dlp,
+            binaries::binary_version,
+            binaries::set_binary_settings,
+            diagnostics::diagnostics,
+            subscriptions::get_subscriptions,
+            subscriptions::add_subscription,
+            subscriptions::update_subscription,
+            subscriptions::remove_subscription,
+            subscriptions::check_subscriptions,
+            subtitles::subtitle_languages,
+            chapters::retreive_chapters,
+            set_download_options,
+            notifications::queue_finished,
             settings::get_settings,
+            cli::take_launch_args,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
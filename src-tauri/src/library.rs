@@ -0,0 +1,354 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Deserialize;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_shell::ShellExt;
+use yaydl_shared::{LibraryItem, LibraryTags, Tool, YaydlError};
+
+use crate::{binaries, post_process, reveal, AppData};
+
+/// Index of the library, so only changed files are read again on startup
+const LIBRARY_FILE: &str = "library.json";
+const AUDIO_EXTENSIONS: [&str; 7] = ["aac", "flac", "m4a", "mp3", "ogg", "opus", "wav"];
+/// Changes are collected this long before the files are read again
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Audio files of the output directory, kept up to date by a file watcher.
+#[derive(Default)]
+pub struct Library {
+    items: HashMap<PathBuf, LibraryItem>,
+    /// Dropping it stops watching
+    watcher: Option<RecommendedWatcher>,
+}
+
+fn library_file<R: Runtime>(app_handle: &AppHandle<R>) -> PathBuf {
+    app_handle.path().app_data_dir().unwrap().join(LIBRARY_FILE)
+}
+
+/// The index of the last run, files changed since then are read again by [`watch`].
+pub fn load<R: Runtime>(app_handle: &AppHandle<R>) -> Library {
+    let Ok(content) = fs::read_to_string(library_file(app_handle)) else {
+        return Library::default();
+    };
+    match serde_json::from_str::<Vec<LibraryItem>>(&content) {
+        Ok(items) => Library {
+            items: items.into_iter().map(|item| (item.path.clone(), item)).collect(),
+            watcher: None,
+        },
+        Err(e) => {
+            println!("{}", e);
+            Library::default()
+        }
+    }
+}
+
+fn save(path: &Path, items: &[LibraryItem]) {
+    let result = serde_json::to_string(items)
+        .map_err(|e| e.to_string())
+        .and_then(|content| {
+            fs::create_dir_all(path.parent().unwrap()).map_err(|e| e.to_string())?;
+            fs::write(path, content).map_err(|e| e.to_string())
+        });
+    if let Err(e) = result {
+        println!("{}", e);
+    }
+}
+
+#[derive(Deserialize)]
+struct Probe {
+    format: ProbeFormat,
+}
+
+#[derive(Deserialize)]
+struct ProbeFormat {
+    duration: Option<String>,
+    #[serde(default)]
+    tags: HashMap<String, String>,
+}
+
+/// Hidden files are the temporary outputs of tagging and post-processing.
+fn is_audio(path: &Path) -> bool {
+    let hidden = path
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+    let audio = path
+        .extension()
+        .is_some_and(|ext| AUDIO_EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str()));
+    audio && !hidden
+}
+
+fn walk(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.filter_map(|e| e.ok().map(|e| e.path())) {
+        if path.is_dir() {
+            walk(&path, files);
+        } else if is_audio(&path) {
+            files.push(path);
+        }
+    }
+}
+
+/// Video a file was downloaded from, by the queue or else the tags yt-dlp embeds.
+fn source<R: Runtime>(app_handle: &AppHandle<R>, path: &Path, tags: &HashMap<String, String>) -> Option<String> {
+    let from_queue = app_handle
+        .state::<Mutex<AppData>>()
+        .lock()
+        .unwrap()
+        .download_list
+        .iter()
+        .find(|d| {
+            d.output_path
+                .as_deref()
+                .is_some_and(|output| output == path || Some(output) == path.parent())
+        })
+        .map(|d| d.metadata.url.clone());
+    from_queue.or_else(|| {
+        ["purl", "comment"]
+            .iter()
+            .filter_map(|key| tags.get(*key))
+            .find(|value| value.starts_with("http"))
+            .cloned()
+    })
+}
+
+async fn probe<R: Runtime>(app_handle: &AppHandle<R>, ffprobe: &Path, path: &Path) -> Result<LibraryItem, YaydlError> {
+    let failed = || YaydlError::ProbeFailed(path.display().to_string());
+    let metadata = fs::metadata(path).map_err(|_| failed())?;
+    let output = app_handle
+        .shell()
+        .command(ffprobe)
+        .args(["-v", "error", "-show_entries", "format=duration:format_tags", "-of", "json"])
+        .arg(path)
+        .output()
+        .await
+        .map_err(|e| YaydlError::TauriShellError(e.to_string()))?;
+    if !output.status.success() {
+        return Err(failed());
+    }
+    let format = serde_json::from_slice::<Probe>(&output.stdout).map_err(|_| failed())?.format;
+    // Vorbis comments are upper case
+    let tags: HashMap<String, String> = format
+        .tags
+        .into_iter()
+        .map(|(key, value)| (key.to_lowercase(), value))
+        .collect();
+    let tag = |key: &str| tags.get(key).cloned().unwrap_or_default();
+    let mut title = tag("title");
+    if title.is_empty() {
+        title = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    }
+    Ok(LibraryItem {
+        path: path.to_path_buf(),
+        tags: LibraryTags {
+            title,
+            artist: tag("artist"),
+            album_artist: tag("album_artist"),
+            album: tag("album"),
+        },
+        duration: format.duration.and_then(|d| d.parse::<f64>().ok()).map(|d| d as u32),
+        size: metadata.len(),
+        modified: modified(&metadata),
+        source: source(app_handle, path, &tags),
+    })
+}
+
+fn modified(metadata: &fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_secs())
+}
+
+fn items<R: Runtime>(app_handle: &AppHandle<R>) -> Vec<LibraryItem> {
+    let mut items: Vec<LibraryItem> = app_handle
+        .state::<Mutex<AppData>>()
+        .lock()
+        .unwrap()
+        .library
+        .items
+        .values()
+        .cloned()
+        .collect();
+    items.sort_by(|a, b| a.group().cmp(b.group()).then_with(|| a.tags.title.cmp(&b.tags.title)));
+    items
+}
+
+/// Reads changed files again and forgets removed ones, including removed directories.
+async fn update<R: Runtime>(app_handle: &AppHandle<R>, paths: Vec<PathBuf>) {
+    let state = app_handle.state::<Mutex<AppData>>();
+    let binaries = state.lock().unwrap().settings.binaries.clone();
+    let ffprobe = match binaries::resolve(app_handle, Tool::Ffprobe, binaries::source(&binaries, Tool::Ffprobe)) {
+        Ok(ffprobe) => ffprobe,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+
+    for path in paths {
+        if !path.exists() {
+            state
+                .lock()
+                .unwrap()
+                .library
+                .items
+                .retain(|file, _| !file.starts_with(&path));
+            continue;
+        }
+        if !path.is_file() || !is_audio(&path) {
+            continue;
+        }
+        let unchanged = fs::metadata(&path).ok().is_some_and(|metadata| {
+            state
+                .lock()
+                .unwrap()
+                .library
+                .items
+                .get(&path)
+                .is_some_and(|item| item.modified == modified(&metadata) && item.size == metadata.len())
+        });
+        if unchanged {
+            continue;
+        }
+        match probe(app_handle, &ffprobe, &path).await {
+            Ok(item) => {
+                state.lock().unwrap().library.items.insert(path, item);
+            }
+            Err(e) => println!("{}", e),
+        }
+    }
+    let items = items(app_handle);
+    save(&library_file(app_handle), &items);
+    let _ = app_handle.emit("library", items);
+}
+
+async fn scan<R: Runtime>(app_handle: &AppHandle<R>) {
+    let (dir, mut paths) = {
+        let state = app_handle.state::<Mutex<AppData>>();
+        let state = state.lock().unwrap();
+        let known: Vec<PathBuf> = state.library.items.keys().cloned().collect();
+        (state.settings.output_dir.clone(), known)
+    };
+    walk(&dir, &mut paths);
+    paths.sort();
+    paths.dedup();
+    update(app_handle, paths).await;
+}
+
+/// Watches the output directory, called again whenever it changes.
+pub fn watch<R: Runtime>(app_handle: &AppHandle<R>) {
+    let state = app_handle.state::<Mutex<AppData>>();
+    let dir = state.lock().unwrap().settings.output_dir.clone();
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Vec<PathBuf>>();
+    let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if let Ok(event) = event {
+            let _ = tx.send(event.paths);
+        }
+    })
+    .and_then(|mut watcher| watcher.watch(&dir, RecursiveMode::Recursive).map(|_| watcher));
+    if let Err(e) = &watcher {
+        println!("{}", e);
+    }
+    {
+        let mut state = state.lock().unwrap();
+        state.library.watcher = watcher.ok();
+        // Files of the previous directory
+        state.library.items.retain(|path, _| path.starts_with(&dir));
+    }
+
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        scan(&app_handle).await;
+        // Ends once the watcher is dropped
+        while let Some(mut paths) = rx.recv().await {
+            tokio::time::sleep(DEBOUNCE).await;
+            while let Ok(more) = rx.try_recv() {
+                paths.extend(more);
+            }
+            paths.sort();
+            paths.dedup();
+            update(&app_handle, paths).await;
+        }
+    });
+}
+
+/// Only files of the library may be changed through its commands.
+pub fn known(state: &Mutex<AppData>, path: &Path) -> Result<(), YaydlError> {
+    if state.lock().unwrap().library.items.contains_key(path) {
+        Ok(())
+    } else {
+        Err(YaydlError::NotInLibrary(path.display().to_string()))
+    }
+}
+
+#[tauri::command]
+pub fn get_library<R: Runtime>(app_handle: AppHandle<R>) -> Vec<LibraryItem> {
+    items(&app_handle)
+}
+
+#[tauri::command]
+pub async fn rescan_library<R: Runtime>(app_handle: AppHandle<R>) -> Vec<LibraryItem> {
+    scan(&app_handle).await;
+    items(&app_handle)
+}
+
+/// Shows the file in the file manager, selected where the platform supports it.
+#[tauri::command]
+pub fn reveal_file<R: Runtime>(
+    path: PathBuf,
+    app_handle: AppHandle<R>,
+    state: tauri::State<'_, Mutex<AppData>>,
+) -> Result<(), YaydlError> {
+    known(&state, &path)?;
+    reveal(&app_handle, &path)
+}
+
+#[tauri::command]
+pub async fn retag_file<R: Runtime>(
+    path: PathBuf,
+    tags: LibraryTags,
+    app_handle: AppHandle<R>,
+    state: tauri::State<'_, Mutex<AppData>>,
+) -> Result<Vec<LibraryItem>, YaydlError> {
+    known(&state, &path)?;
+    let ffmpeg = post_process::ffmpeg(&app_handle)?;
+    let mut args = vec!["-c".to_string(), "copy".into()];
+    for (key, value) in [
+        ("title", &tags.title),
+        ("artist", &tags.artist),
+        ("album_artist", &tags.album_artist),
+        ("album", &tags.album),
+    ] {
+        args.extend(["-metadata".into(), format!("{key}={value}")]);
+    }
+    post_process::rewrite(&app_handle, &ffmpeg, &path, args)
+        .await
+        .map_err(|_| YaydlError::TaggingFailed)?;
+    update(&app_handle, vec![path]).await;
+    Ok(items(&app_handle))
+}
+
+#[tauri::command]
+pub async fn delete_file<R: Runtime>(
+    path: PathBuf,
+    app_handle: AppHandle<R>,
+    state: tauri::State<'_, Mutex<AppData>>,
+) -> Result<Vec<LibraryItem>, YaydlError> {
+    known(&state, &path)?;
+    fs::remove_file(&path).map_err(|_| YaydlError::DeleteFailed(path.display().to_string()))?;
+    state.lock().unwrap().library.items.remove(&path);
+    let items = items(&app_handle);
+    save(&library_file(&app_handle), &items);
+    let _ = app_handle.emit("library", items.clone());
+    Ok(items)
+}
@@ -0,0 +1,133 @@
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_shell::ShellExt;
+use yaydl_shared::{Loudness, LoudnessMode, LoudnessSettings, YaydlError};
+
+use crate::post_process::{ffmpeg, rewrite};
+
+/// Reference level of ReplayGain 2.0
+const REPLAYGAIN_REFERENCE: f32 = -18.0;
+const LOUDNESS_RANGE: f32 = 11.0;
+/// Containers whose muxer drops unknown tags like `REPLAYGAIN_TRACK_GAIN` by default
+const MP4_EXTENSIONS: [&str; 4] = ["m4a", "m4b", "mov", "mp4"];
+
+/// Summary printed by `loudnorm=print_format=json`, all values are strings.
+#[derive(Deserialize)]
+struct Measurement {
+    input_i: String,
+    input_tp: String,
+    input_lra: String,
+    input_thresh: String,
+    target_offset: String,
+}
+
+impl Measurement {
+    fn loudness(&self) -> Option<Loudness> {
+        Some(Loudness {
+            integrated: self.input_i.parse().ok()?,
+            true_peak: self.input_tp.parse().ok()?,
+            range: self.input_lra.parse().ok()?,
+            threshold: self.input_thresh.parse().ok()?,
+        })
+    }
+}
+
+/// First pass, the summary is the last JSON object on stderr.
+async fn measure<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    ffmpeg: &Path,
+    file: &Path,
+    settings: &LoudnessSettings,
+) -> Result<Measurement, YaydlError> {
+    let output = app_handle
+        .shell()
+        .command(ffmpeg)
+        .args(["-hide_banner", "-nostats", "-i"])
+        .arg(file)
+        .arg("-af")
+        .arg(format!(
+            "loudnorm=I={}:TP={}:LRA={LOUDNESS_RANGE}:print_format=json",
+            settings.target, settings.true_peak
+        ))
+        .args(["-f", "null", "-"])
+        .output()
+        .await
+        .map_err(|e| YaydlError::TauriShellError(e.to_string()))?;
+    if !output.status.success() {
+        return Err(YaydlError::LoudnessFailed);
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let start = stderr.rfind('{').ok_or(YaydlError::LoudnessFailed)?;
+    let end = stderr.rfind('}').ok_or(YaydlError::LoudnessFailed)?;
+    serde_json::from_str(stderr.get(start..=end).ok_or(YaydlError::LoudnessFailed)?)
+        .map_err(|_| YaydlError::LoudnessFailed)
+}
+
+fn is_mp4(file: &Path) -> bool {
+    file.extension()
+        .is_some_and(|ext| MP4_EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str()))
+}
+
+/// Normalizes or tags every file, returns the loudness of the first one.
+pub async fn process<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    files: &[PathBuf],
+    settings: &LoudnessSettings,
+) -> Result<Option<Loudness>, YaydlError> {
+    if settings.mode == LoudnessMode::Off || files.is_empty() {
+        return Ok(None);
+    }
+    let ffmpeg = ffmpeg(app_handle)?;
+
+    let mut first = None;
+    for file in files {
+        let measurement = measure(app_handle, &ffmpeg, file, settings).await?;
+        let loudness = measurement.loudness().ok_or(YaydlError::LoudnessFailed)?;
+        let args = match settings.mode {
+            LoudnessMode::Off => unreachable!(),
+            // loudnorm upsamples to 192 kHz, 48 kHz is supported by every output format
+            LoudnessMode::Normalize => vec![
+                "-af".to_string(),
+                format!(
+                    "loudnorm=I={}:TP={}:LRA={LOUDNESS_RANGE}:measured_I={}:measured_TP={}:measured_LRA={}:measured_thresh={}:offset={}:linear=true",
+                    settings.target,
+                    settings.true_peak,
+                    measurement.input_i,
+                    measurement.input_tp,
+                    measurement.input_lra,
+                    measurement.input_thresh,
+                    measurement.target_offset,
+                ),
+                "-ar".into(),
+                "48000".into(),
+            ],
+            LoudnessMode::ReplayGain => {
+                let mut args = vec![
+                    "-c".to_string(),
+                    "copy".into(),
+                    "-metadata".into(),
+                    format!(
+                        "REPLAYGAIN_TRACK_GAIN={:.2} dB",
+                        REPLAYGAIN_REFERENCE - loudness.integrated
+                    ),
+                    "-metadata".into(),
+                    format!(
+                        "REPLAYGAIN_TRACK_PEAK={:.6}",
+                        10f32.powf(loudness.true_peak / 20.0)
+                    ),
+                ];
+                if is_mp4(file) {
+                    args.extend(["-movflags".into(), "use_metadata_tags".into()]);
+                }
+                args
+            }
+        };
+        rewrite(app_handle, &ffmpeg, file, args)
+            .await
+            .map_err(|_| YaydlError::LoudnessFailed)?;
+        first.get_or_insert(loudness);
+    }
+    Ok(first)
+}
@@ -0,0 +1,97 @@
+use std::{path::PathBuf, sync::Mutex};
+
+use notify_rust::Notification;
+use tauri::{AppHandle, Manager, Runtime};
+use yaydl_shared::DownloadState;
+
+use crate::AppData;
+
+pub fn notify_download_finished<R: Runtime>(app_handle: &AppHandle<R>, id: &str) {
+    let state = app_handle.state::<Mutex<AppData>>();
+    let (enabled, title, output_path) = {
+        let state = state.lock().unwrap();
+        let download = state.download_list.iter().find(|d| d.metadata.id == id);
+        (
+            state.settings.notifications.download_finished,
+            download.map(|d| d.metadata.title.clone()).unwrap_or_default(),
+            download.and_then(|d| d.output_path.clone()),
+        )
+    };
+    if enabled {
+        show(app_handle, "Download finished", &title, output_path);
+    }
+}
+
+pub fn notify_download_failed<R: Runtime>(app_handle: &AppHandle<R>, id: &str) {
+    let state = app_handle.state::<Mutex<AppData>>();
+    let (enabled, title) = {
+        let state = state.lock().unwrap();
+        let download = state.download_list.iter().find(|d| d.metadata.id == id);
+        (
+            state.settings.notifications.download_failed,
+            download.map(|d| d.metadata.title.clone()).unwrap_or_default(),
+        )
+    };
+    if enabled {
+        show(app_handle, "Download failed", &title, None);
+    }
+}
+
+/// Called by the frontend once a queue run is through, `ids` are the downloads of that run.
+#[tauri::command]
+pub fn queue_finished<R: Runtime>(ids: Vec<String>, app_handle: AppHandle<R>, state: tauri::State<'_, Mutex<AppData>>) {
+    let (enabled, finished, failed, output_dir) = {
+        let state = state.lock().unwrap();
+        let count = |download_state: DownloadState| {
+            state
+                .download_list
+                .iter()
+                .filter(|d| ids.contains(&d.metadata.id) && d.download_state == download_state)
+                .count()
+        };
+        (
+            state.settings.notifications.queue_finished,
+            count(DownloadState::Finished),
+            count(DownloadState::Failure) + count(DownloadState::VerificationFailed),
+            state.settings.output_dir.clone(),
+        )
+    };
+    if enabled {
+        let body = format!("{finished} finished, {failed} failed");
+        show(&app_handle, "Queue finished", &body, Some(output_dir));
+    }
+}
+
+/// Shows a native notification. Only on Linux clicking it reveals `reveal` in the file manager,
+/// notify-rust doesn't report clicks on Windows and macOS, so there it is informational only.
+fn show<R: Runtime>(app_handle: &AppHandle<R>, summary: &str, body: &str, reveal: Option<PathBuf>) {
+    let mut notification = Notification::new();
+    notification.appname("yaydl").summary(summary).body(body);
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if let Some(path) = reveal {
+        notification.action("default", "Show in folder");
+        match notification.show() {
+            Ok(handle) => {
+                let app_handle = app_handle.clone();
+                std::thread::spawn(move || {
+                    handle.wait_for_action(|action| {
+                        if action == "default" {
+                            if let Err(e) = crate::reveal(&app_handle, &path) {
+                                println!("{}", e);
+                            }
+                        }
+                    })
+                });
+            }
+            Err(e) => println!("{}", e),
+        }
+        return;
+    }
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    let _ = (app_handle, reveal);
+
+    if let Err(e) = notification.show() {
+        println!("{}", e);
+    }
+}
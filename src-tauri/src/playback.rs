@@ -0,0 +1,193 @@
+use std::{fs, path::PathBuf, sync::Mutex, time::Instant};
+
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_shell::{process::CommandChild, process::CommandEvent, ShellExt};
+use yaydl_shared::{Playback, Tool, YaydlError};
+
+use crate::{binaries, library, ytdlp, AppData};
+
+/// Length of a preview in seconds
+const PREVIEW_LENGTH: u32 = 15;
+
+/// The running ffplay child, seeking restarts it at the new position.
+pub struct Player {
+    playback: Playback,
+    /// Stream URL or path of the file
+    source: String,
+    started: Instant,
+    child: CommandChild,
+}
+
+impl Player {
+    fn position(&self) -> u32 {
+        self.playback.position + self.started.elapsed().as_secs() as u32
+    }
+}
+
+fn emit<R: Runtime>(app_handle: &AppHandle<R>, playback: Option<Playback>) {
+    let _ = app_handle.emit("playback", playback);
+}
+
+/// Stops the running ffplay without emitting an event.
+fn kill(state: &Mutex<AppData>) {
+    if let Some(player) = state.lock().unwrap().player.take() {
+        let _ = player.child.kill();
+    }
+}
+
+async fn start<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    playback: Playback,
+    source: String,
+) -> Result<Playback, YaydlError> {
+    let state = app_handle.state::<Mutex<AppData>>();
+    kill(&state);
+    let binaries = state.lock().unwrap().settings.binaries.clone();
+    let ffplay = binaries::resolve(app_handle, Tool::Ffplay, binaries::source(&binaries, Tool::Ffplay))?;
+
+    let mut args = vec![
+        "-nodisp".to_string(),
+        "-autoexit".into(),
+        "-loglevel".into(),
+        "error".into(),
+        "-ss".into(),
+        playback.position.to_string(),
+    ];
+    if let Some(end) = playback.end {
+        args.extend(["-t".into(), end.saturating_sub(playback.position).to_string()]);
+    }
+    args.extend(["-i".into(), source.clone()]);
+    let (mut rx, child) = app_handle
+        .shell()
+        .command(ffplay)
+        .args(args)
+        .spawn()
+        .map_err(|_| YaydlError::PlaybackFailed)?;
+    let pid = child.pid();
+    state.lock().unwrap().player = Some(Player {
+        playback: playback.clone(),
+        source,
+        started: Instant::now(),
+        child,
+    });
+
+    // Clears the player once ffplay exits on its own
+    let handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            if let CommandEvent::Terminated(_) = event {
+                break;
+            }
+        }
+        let state = handle.state::<Mutex<AppData>>();
+        let mut state = state.lock().unwrap();
+        if state.player.as_ref().is_some_and(|p| p.child.pid() == pid) {
+            state.player = None;
+            emit(&handle, None);
+        }
+    });
+    emit(app_handle, Some(playback.clone()));
+    Ok(playback)
+}
+
+/// First audio file of a download, the first track if it was split by chapter.
+fn output_file(path: PathBuf) -> Option<PathBuf> {
+    if path.is_file() {
+        return Some(path);
+    }
+    let mut tracks: Vec<PathBuf> = fs::read_dir(path)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && !p.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.')))
+        .collect();
+    tracks.sort();
+    tracks.into_iter().next()
+}
+
+/// Streams a few seconds of the source, starting where the first time range starts.
+#[tauri::command]
+pub async fn preview_download<R: Runtime>(id: String, app_handle: AppHandle<R>) -> Result<Playback, YaydlError> {
+    let (url, position) = app_handle
+        .state::<Mutex<AppData>>()
+        .lock()
+        .unwrap()
+        .download_list
+        .iter()
+        .find(|d| d.metadata.id == id)
+        .map(|d| (d.metadata.url.clone(), d.options.time_ranges.first().map_or(0, |r| r.start)))
+        .ok_or(YaydlError::NothingToPlay)?;
+    let output = ytdlp::yt_dlp(&app_handle)?
+        .args(["-f", "bestaudio", "--get-url", &url])
+        .output()
+        .await
+        .map_err(|e| YaydlError::TauriShellError(e.to_string()))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stream = match stdout.lines().next() {
+        Some(stream) if output.status.success() => stream.trim().to_string(),
+        _ => return Err(YaydlError::PlaybackFailed),
+    };
+    let playback = Playback {
+        id,
+        preview: true,
+        position,
+        end: Some(position + PREVIEW_LENGTH),
+    };
+    start(&app_handle, playback, stream).await
+}
+
+#[tauri::command]
+pub async fn play_output<R: Runtime>(id: String, app_handle: AppHandle<R>) -> Result<Playback, YaydlError> {
+    let path = app_handle
+        .state::<Mutex<AppData>>()
+        .lock()
+        .unwrap()
+        .download_list
+        .iter()
+        .find(|d| d.metadata.id == id)
+        .and_then(|d| d.output_path.clone())
+        .and_then(output_file)
+        .ok_or(YaydlError::NothingToPlay)?;
+    let playback = Playback {
+        id,
+        preview: false,
+        position: 0,
+        end: None,
+    };
+    start(&app_handle, playback, path.display().to_string()).await
+}
+
+/// Plays a file of the library, its path is the ID of the playback.
+#[tauri::command]
+pub async fn play_file<R: Runtime>(path: PathBuf, app_handle: AppHandle<R>) -> Result<Playback, YaydlError> {
+    library::known(&app_handle.state::<Mutex<AppData>>(), &path)?;
+    let playback = Playback {
+        id: path.display().to_string(),
+        preview: false,
+        position: 0,
+        end: None,
+    };
+    start(&app_handle, playback, path.display().to_string()).await
+}
+
+/// Jumps `offset` seconds back or forward, seeking past the end of a preview stops it.
+#[tauri::command]
+pub async fn seek_playback<R: Runtime>(offset: i32, app_handle: AppHandle<R>) -> Result<Option<Playback>, YaydlError> {
+    let (mut playback, source, position) = {
+        let state = app_handle.state::<Mutex<AppData>>();
+        let state = state.lock().unwrap();
+        let player = state.player.as_ref().ok_or(YaydlError::NothingToPlay)?;
+        (player.playback.clone(), player.source.clone(), player.position())
+    };
+    playback.position = position.saturating_add_signed(offset);
+    if playback.end.is_some_and(|end| playback.position >= end) {
+        stop_playback(app_handle);
+        return Ok(None);
+    }
+    start(&app_handle, playback, source).await.map(Some)
+}
+
+#[tauri::command]
+pub fn stop_playback<R: Runtime>(app_handle: AppHandle<R>) {
+    kill(&app_handle.state::<Mutex<AppData>>());
+    emit(&app_handle, None);
+}
@@ -0,0 +1,365 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_shell::ShellExt;
+use yaydl_shared::{
+    DownloadState, LoudnessMode, LoudnessSettings, Metadata, PostProcessStep, SettingsError, Tool, YaydlError,
+    AUDIO_FORMATS,
+};
+
+use crate::{binaries, loudness, schedule, AppData};
+
+const SILENCE_THRESHOLD: &str = "-50dB";
+/// Shorter pauses at the start and end are kept
+const SILENCE_MIN_DURATION: f64 = 0.1;
+/// Seconds a silence may be away from the start or end and still count as being there
+const EDGE: f64 = 0.05;
+
+pub fn ffmpeg<R: Runtime>(app_handle: &AppHandle<R>) -> Result<PathBuf, YaydlError> {
+    let source = app_handle
+        .state::<Mutex<AppData>>()
+        .lock()
+        .unwrap()
+        .settings
+        .binaries
+        .ffmpeg
+        .clone();
+    Ok(binaries::resolve(app_handle, Tool::Ffmpeg, &source)?)
+}
+
+/// Runs ffmpeg from `file` into `output`, the error is the last line ffmpeg printed.
+async fn run_ffmpeg<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    ffmpeg: &Path,
+    file: &Path,
+    args: Vec<String>,
+    output: &Path,
+) -> Result<(), String> {
+    let result = app_handle
+        .shell()
+        .command(ffmpeg)
+        .args(["-y", "-loglevel", "error", "-i"])
+        .arg(file)
+        .args(args)
+        .arg(output)
+        .output()
+        .await
+        .map_err(|e| e.to_string())?;
+    if result.status.success() {
+        Ok(())
+    } else {
+        let _ = fs::remove_file(output);
+        Err(last_line(&result.stderr))
+    }
+}
+
+/// Writes to a hidden sibling first so a failed run leaves the file untouched.
+/// Video, cover art and subtitle streams are copied, only the audio is re-encoded.
+pub async fn rewrite<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    ffmpeg: &Path,
+    file: &Path,
+    args: Vec<String>,
+) -> Result<(), String> {
+    let file_name = file.file_name().unwrap().to_string_lossy();
+    let rewritten = file.with_file_name(format!(".processing-{file_name}"));
+    let mut all_args = vec![
+        "-map".to_string(),
+        "0".into(),
+        "-map_metadata".into(),
+        "0".into(),
+        "-c:v".into(),
+        "copy".into(),
+        "-c:s".into(),
+        "copy".into(),
+    ];
+    all_args.extend(args);
+    run_ffmpeg(app_handle, ffmpeg, file, all_args, &rewritten).await?;
+    fs::rename(&rewritten, file).map_err(|e| e.to_string())
+}
+
+fn last_line(output: &[u8]) -> String {
+    String::from_utf8_lossy(output)
+        .lines()
+        .rfind(|l| !l.trim().is_empty())
+        .unwrap_or("exited with an error")
+        .to_string()
+}
+
+fn extension(format: &str) -> &str {
+    match format {
+        "vorbis" => "ogg",
+        "alac" => "m4a",
+        format => format,
+    }
+}
+
+/// Replaces the placeholders in `template` with values of the file and its video.
+fn fill(template: &str, file: &Path, metadata: &Metadata) -> String {
+    fill_with(template, file, metadata, str::to_string)
+}
+
+/// Like [`fill`], but the values of the video can't add directories or leave the one of the template.
+fn fill_path(template: &str, file: &Path, metadata: &Metadata) -> PathBuf {
+    PathBuf::from(fill_with(template, file, metadata, path_component))
+}
+
+fn fill_with(template: &str, file: &Path, metadata: &Metadata, escape: fn(&str) -> String) -> String {
+    let part = |part: Option<&std::ffi::OsStr>| part.map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
+    template
+        .replace("{path}", &file.display().to_string())
+        .replace("{dir}", &part(file.parent().map(Path::as_os_str)))
+        .replace("{name}", &part(file.file_stem()))
+        .replace("{ext}", &part(file.extension()))
+        .replace("{title}", &escape(&metadata.title))
+        .replace("{id}", &escape(&metadata.id))
+        .replace("{url}", &escape(&metadata.url))
+}
+
+/// Replaces separators and characters Windows doesn't allow in file names.
+fn path_component(value: &str) -> String {
+    let component: String = value
+        .chars()
+        .map(|c| if "/\\<>:\"|?*".contains(c) || c.is_control() { '_' } else { c })
+        .collect();
+    match component.trim() {
+        "" | "." | ".." => "_".to_string(),
+        _ => component,
+    }
+}
+
+/// `loudness` is the global setting, normalizing in both places would process the files twice.
+pub fn validate(steps: &[PostProcessStep], loudness: &LoudnessSettings) -> Result<(), SettingsError> {
+    if loudness.mode != LoudnessMode::Off && has_normalize(steps) {
+        return Err(SettingsError::DoubleNormalization);
+    }
+    for step in steps {
+        let valid = match step {
+            PostProcessStep::Convert { format } => format != "best" && AUDIO_FORMATS.contains(&format.as_str()),
+            PostProcessStep::Tag { tags } => tags.iter().all(|(key, _)| !key.trim().is_empty()),
+            PostProcessStep::Move { dir } => !dir.trim().is_empty(),
+            PostProcessStep::Command { program, .. } => !program.trim().is_empty(),
+            PostProcessStep::TrimSilence | PostProcessStep::Normalize(_) => true,
+        };
+        if !valid {
+            return Err(SettingsError::InvalidPostProcessStep(step.name().into()));
+        }
+    }
+    Ok(())
+}
+
+pub fn has_normalize(steps: &[PostProcessStep]) -> bool {
+    steps.iter().any(|step| matches!(step, PostProcessStep::Normalize(_)))
+}
+
+/// Streams the audio through `silencedetect`, unlike `areverse` this doesn't hold it in memory.
+async fn detect_silence<R: Runtime>(app_handle: &AppHandle<R>, ffmpeg: &Path, file: &Path) -> Result<String, String> {
+    let output = app_handle
+        .shell()
+        .command(ffmpeg)
+        .args(["-hide_banner", "-i"])
+        .arg(file)
+        .arg("-vn")
+        .arg("-af")
+        .arg(format!("silencedetect=noise={SILENCE_THRESHOLD}:duration={SILENCE_MIN_DURATION}"))
+        .args(["-f", "null", "-"])
+        .output()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(last_line(&output.stderr));
+    }
+    Ok(String::from_utf8_lossy(&output.stderr).into_owned())
+}
+
+/// Seconds of a `HH:MM:SS.ss` timestamp.
+fn parse_clock(clock: &str) -> Option<f64> {
+    let mut seconds = 0.0;
+    for part in clock.split(':') {
+        seconds = seconds * 60.0 + part.parse::<f64>().ok()?;
+    }
+    Some(seconds)
+}
+
+/// Start and end of the audio without leading and trailing silence, from `silencedetect`'s output.
+/// The end is `None` if the audio doesn't end in silence. Silence that lasts until the end has no
+/// `silence_end` in older ffmpeg versions and one at the end of the audio in newer ones.
+fn audible_range(stderr: &str) -> (f64, Option<f64>) {
+    let value = |line: &str, key: &str| {
+        line.split(key)
+            .nth(1)?
+            .split_whitespace()
+            .next()?
+            .parse::<f64>()
+            .ok()
+    };
+    let mut silences: Vec<(f64, Option<f64>)> = Vec::new();
+    let mut duration = None;
+    // Progress is separated by carriage returns
+    for line in stderr.split(['\n', '\r']) {
+        if let Some(start) = value(line, "silence_start: ") {
+            silences.push((start, None));
+        } else if let Some(end) = value(line, "silence_end: ") {
+            if let Some(silence) = silences.last_mut() {
+                silence.1 = Some(end);
+            }
+        } else if let Some(time) = line
+            .split("time=")
+            .nth(1)
+            .and_then(|time| time.split_whitespace().next())
+            .and_then(parse_clock)
+        {
+            duration = Some(time);
+        }
+    }
+    let start = silences
+        .first()
+        .filter(|(start, _)| *start <= EDGE)
+        .and_then(|(_, end)| *end)
+        .unwrap_or(0.0);
+    let end = silences
+        .last()
+        .filter(|(_, end)| end.is_none_or(|end| duration.is_some_and(|duration| end >= duration - EDGE)))
+        .map(|(start, _)| start.max(0.0));
+    (start, end)
+}
+
+/// Runs `step` on `file`, which is updated if the step changes where the file is.
+async fn run_step<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    ffmpeg: &Path,
+    step: &PostProcessStep,
+    file: &mut PathBuf,
+    metadata: &Metadata,
+) -> Result<(), String> {
+    match step {
+        PostProcessStep::Convert { format } => {
+            let converted = file.with_extension(extension(format));
+            if converted == *file {
+                return Ok(());
+            }
+            let mut args = vec!["-map".to_string(), "0:a".into(), "-map_metadata".into(), "0".into()];
+            if format == "alac" {
+                args.extend(["-c:a".into(), "alac".into()]);
+            }
+            run_ffmpeg(app_handle, ffmpeg, file, args, &converted).await?;
+            let _ = fs::remove_file(&*file);
+            *file = converted;
+        }
+        PostProcessStep::TrimSilence => {
+            let (start, end) = audible_range(&detect_silence(app_handle, ffmpeg, file).await?);
+            // Nothing to trim, or nothing but silence
+            if (start <= 0.0 && end.is_none()) || end.is_some_and(|end| end <= start) {
+                return Ok(());
+            }
+            let end = end.map(|end| format!(":end={end}")).unwrap_or_default();
+            let filter = format!("atrim=start={start}{end},asetpts=PTS-STARTPTS");
+            rewrite(app_handle, ffmpeg, file, vec!["-af".into(), filter]).await?;
+        }
+        PostProcessStep::Normalize(settings) => {
+            loudness::process(app_handle, std::slice::from_ref(file), settings)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        PostProcessStep::Tag { tags } => {
+            let mut args = vec!["-c".to_string(), "copy".into()];
+            for (key, value) in tags {
+                args.extend(["-metadata".into(), format!("{key}={}", fill(value, file, metadata))]);
+            }
+            rewrite(app_handle, ffmpeg, file, args).await?;
+        }
+        PostProcessStep::Move { dir } => {
+            let dir = fill_path(dir, file, metadata);
+            fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+            let moved = dir.join(file.file_name().unwrap());
+            // Renaming fails across file systems
+            if fs::rename(&*file, &moved).is_err() {
+                fs::copy(&*file, &moved).map_err(|e| e.to_string())?;
+                let _ = fs::remove_file(&*file);
+            }
+            *file = moved;
+        }
+        PostProcessStep::Command { program, args } => {
+            let output = app_handle
+                .shell()
+                .command(fill(program, file, metadata))
+                .args(args.iter().map(|arg| fill(arg, file, metadata)))
+                .output()
+                .await
+                .map_err(|e| e.to_string())?;
+            if !output.status.success() {
+                return Err(last_line(&output.stderr));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Runs every step on every file, returns where the files ended up.
+pub async fn run<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    id: &str,
+    metadata: &Metadata,
+    mut files: Vec<PathBuf>,
+    steps: &[PostProcessStep],
+) -> Result<Vec<PathBuf>, YaydlError> {
+    if steps.is_empty() || files.is_empty() {
+        return Ok(files);
+    }
+    let ffmpeg = ffmpeg(app_handle)?;
+
+    for (index, step) in steps.iter().enumerate() {
+        let phase = format!("{} ({}/{})", step.name(), index + 1, steps.len());
+        schedule::set_state(app_handle, id, DownloadState::PostProcessing(phase));
+        for file in files.iter_mut() {
+            run_step(app_handle, &ffmpeg, step, file, metadata)
+                .await
+                .map_err(|reason| YaydlError::PostProcessingFailed(step.name().into(), reason))?;
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trims_leading_and_trailing_silence() {
+        let stderr = "[silencedetect @ 0x1] silence_start: 0\n\
+                      [silencedetect @ 0x1] silence_end: 1.5 | silence_duration: 1.5\n\
+                      size=N/A time=00:01:00.00 bitrate=N/A speed= 120x\r\
+                      [silencedetect @ 0x1] silence_start: 60.5\n\
+                      [silencedetect @ 0x1] silence_end: 62 | silence_duration: 2\n\
+                      [silencedetect @ 0x1] silence_start: 200.25\n\
+                      size=N/A time=00:03:25.00 bitrate=N/A speed= 130x\n";
+        assert_eq!(audible_range(stderr), (1.5, Some(200.25)));
+    }
+
+    #[test]
+    fn silence_ending_with_the_audio() {
+        let stderr = "[silencedetect @ 0x1] silence_start: 180\n\
+                      [silencedetect @ 0x1] silence_end: 185.02 | silence_duration: 5.02\n\
+                      size=N/A time=00:03:05.02 bitrate=N/A speed= 130x\n";
+        assert_eq!(audible_range(stderr), (0.0, Some(180.0)));
+    }
+
+    #[test]
+    fn keeps_silence_in_the_middle() {
+        let stderr = "[silencedetect @ 0x1] silence_start: 60\n\
+                      [silencedetect @ 0x1] silence_end: 70 | silence_duration: 10\n\
+                      size=N/A time=00:02:00.00 bitrate=N/A speed= 130x\n";
+        assert_eq!(audible_range(stderr), (0.0, None));
+        assert_eq!(audible_range(""), (0.0, None));
+    }
+
+    #[test]
+    fn parses_clocks() {
+        assert_eq!(parse_clock("00:03:25.50"), Some(205.5));
+        assert_eq!(parse_clock("N/A"), None);
+    }
+}
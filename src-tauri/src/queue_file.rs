@@ -0,0 +1,346 @@
+use std::{fs, mem, path::Path, sync::Mutex};
+
+use serde_json::Value;
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_dialog::DialogExt;
+use yaydl_shared::{
+    Download, DownloadOptions, DownloadState, ImportReport, Metadata, QueueFileError, QueueFileFormat, SkippedEntry,
+    TimeRange, YaydlError,
+};
+
+use crate::{enqueue, AppData};
+
+/// Only `url` is required when importing, `time_ranges` are `start-end` in seconds separated by spaces.
+const CSV_COLUMNS: [&str; 9] = [
+    "id",
+    "url",
+    "title",
+    "uploader",
+    "duration",
+    "thumbnail",
+    "output_format",
+    "time_ranges",
+    "high_priority",
+];
+
+/// A parsed download, or why the entry can't be imported.
+type Entry = Result<Download, SkippedEntry>;
+
+fn skipped(entry: impl Into<String>, reason: impl Into<String>) -> SkippedEntry {
+    SkippedEntry {
+        entry: entry.into(),
+        reason: reason.into(),
+    }
+}
+
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn format_ranges(ranges: &[TimeRange]) -> String {
+    ranges
+        .iter()
+        .map(|r| format!("{}-{}", r.start, r.end.map(|end| end.to_string()).unwrap_or_default()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn parse_ranges(field: &str) -> Option<Vec<TimeRange>> {
+    field
+        .split_whitespace()
+        .map(|range| {
+            let (start, end) = range.split_once('-')?;
+            let end = if end.is_empty() { None } else { Some(end.parse().ok()?) };
+            Some(TimeRange {
+                start: start.parse().ok()?,
+                end,
+            })
+        })
+        .collect()
+}
+
+fn to_csv(downloads: &[Download]) -> String {
+    let mut csv = CSV_COLUMNS.join(",");
+    csv.push('\n');
+    for d in downloads {
+        let fields = [
+            d.metadata.id.clone(),
+            d.metadata.url.clone(),
+            d.metadata.title.clone(),
+            d.metadata.uploader.clone(),
+            d.metadata.duration.clone(),
+            d.metadata.thumbnail.clone(),
+            d.options.output_format.clone().unwrap_or_default(),
+            format_ranges(&d.options.time_ranges),
+            d.high_priority.to_string(),
+        ];
+        csv.push_str(&fields.iter().map(|f| quote(f)).collect::<Vec<_>>().join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Splits CSV into records, quoted fields may contain commas, quotes and line breaks.
+fn parse_csv(content: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => record.push(mem::take(&mut field)),
+            '\n' if !quoted => {
+                record.push(mem::take(&mut field));
+                records.push(mem::take(&mut record));
+            }
+            '\r' if !quoted => {}
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records.retain(|r| r.iter().any(|f| !f.trim().is_empty()));
+    records
+}
+
+fn from_csv(content: &str, name: &str) -> Result<Vec<Entry>, QueueFileError> {
+    let mut records = parse_csv(content).into_iter();
+    let header: Vec<String> = records
+        .next()
+        .unwrap_or_default()
+        .iter()
+        .map(|column| column.trim().to_lowercase())
+        .collect();
+    if !header.iter().any(|column| column == "url") {
+        return Err(QueueFileError::ParsingFailed(name.into()));
+    }
+
+    Ok(records
+        .map(|record| {
+            let field = |column: &str| {
+                header
+                    .iter()
+                    .position(|c| c == column)
+                    .and_then(|i| record.get(i))
+                    .map(|f| f.trim().to_string())
+                    .unwrap_or_default()
+            };
+            let url = field("url");
+            let time_ranges = parse_ranges(&field("time_ranges")).ok_or_else(|| skipped(&url, "Invalid time ranges"))?;
+            Ok(Download {
+                metadata: Metadata {
+                    id: field("id"),
+                    url: url.clone(),
+                    title: field("title"),
+                    uploader: field("uploader"),
+                    duration: field("duration"),
+                    thumbnail: field("thumbnail"),
+                    ..Default::default()
+                },
+                options: DownloadOptions {
+                    output_format: Some(field("output_format")).filter(|f| !f.is_empty()),
+                    time_ranges,
+                    ..Default::default()
+                },
+                high_priority: field("high_priority").parse().unwrap_or(false),
+                ..Default::default()
+            })
+        })
+        .collect())
+}
+
+/// Entries are parsed one by one so a single broken entry doesn't fail the whole file.
+fn from_json(content: &str, name: &str) -> Result<Vec<Entry>, QueueFileError> {
+    let values: Vec<Value> = serde_json::from_str(content).map_err(|_| QueueFileError::ParsingFailed(name.into()))?;
+    Ok(values
+        .into_iter()
+        .map(|value| {
+            serde_json::from_value::<Download>(value.clone()).map_err(|_| {
+                let url = value.pointer("/metadata/url").and_then(Value::as_str);
+                skipped(url.map_or_else(|| value.to_string(), String::from), "Not a queue entry")
+            })
+        })
+        .collect())
+}
+
+/// One URL per line, empty lines and lines starting with `#` are ignored.
+fn from_text(content: &str) -> Vec<Entry> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|url| {
+            Ok(Download {
+                metadata: Metadata {
+                    url: url.to_string(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// Adds the entries in front of the queue in file order, skipping the ones `enqueue` refuses.
+fn merge(state: &Mutex<AppData>, entries: Vec<Entry>) -> ImportReport {
+    let mut state = state.lock().unwrap();
+    let mut report = ImportReport::default();
+    for entry in entries {
+        let mut download = match entry {
+            Ok(download) => download,
+            Err(entry) => {
+                report.skipped.push(entry);
+                continue;
+            }
+        };
+        // Progress, results and paths of the exporting machine don't apply here
+        download.metadata.loading = false;
+        download.download_state = DownloadState::Idle;
+        download.output_path = None;
+        download.loudness = None;
+        download.subscription = None;
+        download.options.output_dir = None;
+
+        let entry = download.metadata.url.clone();
+        match enqueue(&mut state, download, report.added.len()) {
+            Ok(url) => report.added.push(url),
+            Err(e) => report.skipped.push(skipped(entry, e.to_string())),
+        }
+    }
+    report
+}
+
+#[tauri::command]
+pub async fn export_queue<R: Runtime>(
+    format: QueueFileFormat,
+    app_handle: AppHandle<R>,
+    state: tauri::State<'_, Mutex<AppData>>,
+) -> Result<usize, YaydlError> {
+    let downloads = state.lock().unwrap().download_list.clone();
+    if downloads.is_empty() {
+        return Err(QueueFileError::NothingToExport.into());
+    }
+    let Some(path) = app_handle
+        .dialog()
+        .file()
+        .add_filter(format.name(), &[format.extension()])
+        .set_file_name(format!("queue.{}", format.extension()))
+        .blocking_save_file()
+    else {
+        return Err(YaydlError::FileSelectionFailed);
+    };
+    let path = path.as_path().ok_or(YaydlError::FileSelectionFailed)?;
+    let write_failed = || QueueFileError::WriteFailed(path.display().to_string());
+
+    let content = match format {
+        QueueFileFormat::Json => serde_json::to_string_pretty(&downloads).map_err(|_| write_failed())?,
+        QueueFileFormat::Csv => to_csv(&downloads),
+        QueueFileFormat::Text => downloads.iter().map(|d| format!("{}\n", d.metadata.url)).collect(),
+    };
+    fs::write(path, content).map_err(|_| write_failed())?;
+    Ok(downloads.len())
+}
+
+/// The format is picked by the extension, anything but `.json` and `.csv` is read as a URL list.
+#[tauri::command]
+pub async fn import_queue<R: Runtime>(
+    app_handle: AppHandle<R>,
+    state: tauri::State<'_, Mutex<AppData>>,
+) -> Result<(ImportReport, Vec<Download>), YaydlError> {
+    let extensions = QueueFileFormat::ALL.map(|f| f.extension());
+    let Some(path) = app_handle
+        .dialog()
+        .file()
+        .add_filter("Queue", &extensions)
+        .blocking_pick_file()
+    else {
+        return Err(YaydlError::FileSelectionFailed);
+    };
+    let path = path.as_path().ok_or(YaydlError::FileSelectionFailed)?;
+    let name = path.display().to_string();
+    let content = fs::read_to_string(path).map_err(|_| QueueFileError::ReadFailed(name.clone()))?;
+
+    let entries = match extension(path) {
+        Some(QueueFileFormat::Json) => from_json(&content, &name)?,
+        Some(QueueFileFormat::Csv) => from_csv(&content, &name)?,
+        _ => from_text(&content),
+    };
+    let report = merge(&state, entries);
+    let downloads = state.lock().unwrap().download_list.clone();
+    Ok((report, downloads))
+}
+
+fn extension(path: &Path) -> Option<QueueFileFormat> {
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+    QueueFileFormat::ALL.into_iter().find(|f| f.extension() == extension)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoted_fields_round_trip() {
+        let fields = ["plain", "with, comma", "with \"quotes\"", "two\nlines", ""];
+        let line = fields.iter().map(|f| quote(f)).collect::<Vec<_>>().join(",");
+        assert_eq!(parse_csv(&format!("{line}\n")), vec![fields.map(String::from).to_vec()]);
+    }
+
+    #[test]
+    fn skips_empty_lines_and_carriage_returns() {
+        let records = parse_csv("url,title\r\n\r\nhttps://example.com,a\r\n");
+        assert_eq!(records, vec![vec!["url", "title"], vec!["https://example.com", "a"]]);
+    }
+
+    #[test]
+    fn ranges_round_trip() {
+        let ranges = vec![
+            TimeRange { start: 30, end: Some(90) },
+            TimeRange { start: 120, end: None },
+        ];
+        assert_eq!(format_ranges(&ranges), "30-90 120-");
+        assert_eq!(parse_ranges(&format_ranges(&ranges)), Some(ranges));
+        assert_eq!(parse_ranges(""), Some(Vec::new()));
+        assert_eq!(parse_ranges("30"), None);
+        assert_eq!(parse_ranges("a-b"), None);
+    }
+
+    #[test]
+    fn csv_round_trip() {
+        let download = Download {
+            metadata: Metadata {
+                id: "abc".into(),
+                url: "https://www.youtube.com/watch?v=abc".into(),
+                title: "Title, \"quoted\"".into(),
+                uploader: "Someone".into(),
+                duration: "3:25".into(),
+                ..Default::default()
+            },
+            options: DownloadOptions {
+                output_format: Some("flac".into()),
+                time_ranges: vec![TimeRange { start: 10, end: Some(20) }],
+                ..Default::default()
+            },
+            high_priority: true,
+            ..Default::default()
+        };
+        let entries = from_csv(&to_csv(std::slice::from_ref(&download)), "queue.csv").unwrap();
+        let imported = entries.into_iter().next().unwrap().unwrap();
+        assert_eq!(imported.metadata.title, download.metadata.title);
+        assert_eq!(imported.metadata.uploader, download.metadata.uploader);
+        assert_eq!(imported.options, download.options);
+        assert!(imported.high_priority);
+    }
+}
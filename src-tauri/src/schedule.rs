@@ -0,0 +1,166 @@
+use std::{sync::Mutex, time::Duration};
+
+use chrono::{Local, NaiveTime};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use yaydl_shared::{DownloadState, DownloadStateArgs, ScheduleWindow, SettingsError, YaydlError};
+
+use crate::{cancelled, AppData};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+pub fn parse_time(time: &str) -> Result<NaiveTime, SettingsError> {
+    NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| SettingsError::InvalidTime(time.into()))
+}
+
+/// Checks a `--limit-rate` value, e.g. `500K` or `4.2M`.
+pub fn validate_rate_limit(rate_limit: &str) -> Result<(), SettingsError> {
+    let number = rate_limit
+        .strip_suffix(['K', 'M', 'G', 'k', 'm', 'g'])
+        .unwrap_or(rate_limit);
+    match number.parse::<f32>() {
+        Ok(rate) if rate > 0.0 && rate.is_finite() => Ok(()),
+        _ => Err(SettingsError::InvalidRateLimit(rate_limit.into())),
+    }
+}
+
+/// Whether downloads may run at `now`, always true without any windows.
+/// A window that ends when it starts is open all day.
+pub fn in_window(windows: &[ScheduleWindow], now: NaiveTime) -> bool {
+    windows.is_empty()
+        || windows.iter().any(|window| {
+            let (Ok(start), Ok(end)) = (parse_time(&window.start), parse_time(&window.end)) else {
+                return false;
+            };
+            if start == end {
+                true
+            } else if start < end {
+                start <= now && now < end
+            } else {
+                now >= start || now < end
+            }
+        })
+}
+
+/// Holds a download in [`DownloadState::Scheduled`] until a schedule window opens.
+pub async fn wait_for_window<R: Runtime>(app_handle: &AppHandle<R>, id: &str) -> Result<(), YaydlError> {
+    let state = app_handle.state::<Mutex<AppData>>();
+    if in_window(&state.lock().unwrap().settings.schedule, Local::now().time()) {
+        return Ok(());
+    }
+
+    state.lock().unwrap().scheduled.insert(id.to_string());
+    set_state(app_handle, id, DownloadState::Scheduled);
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        let mut state = state.lock().unwrap();
+        // `stop_all` and removing the download clear the waiting downloads
+        if !state.scheduled.contains(id) {
+            return Err(cancelled(&state, id));
+        }
+        if in_window(&state.settings.schedule, Local::now().time()) {
+            state.scheduled.remove(id);
+            break;
+        }
+    }
+    set_state(app_handle, id, DownloadState::Loading(0));
+    Ok(())
+}
+
+/// Shows every download of a starting queue as waiting while no window is open,
+/// not only the one [`wait_for_window`] holds.
+#[tauri::command]
+pub fn mark_scheduled<R: Runtime>(ids: Vec<String>, app_handle: AppHandle<R>) {
+    let schedule = app_handle.state::<Mutex<AppData>>().lock().unwrap().settings.schedule.clone();
+    if in_window(&schedule, Local::now().time()) {
+        return;
+    }
+    for id in ids {
+        set_state(&app_handle, &id, DownloadState::Scheduled);
+    }
+}
+
+pub fn set_state<R: Runtime>(app_handle: &AppHandle<R>, id: &str, download_state: DownloadState) {
+    if let Some(download) = app_handle
+        .state::<Mutex<AppData>>()
+        .lock()
+        .unwrap()
+        .download_list
+        .iter_mut()
+        .find(|d| d.metadata.id == id)
+    {
+        download.download_state = download_state.clone();
+    }
+    let _ = app_handle.emit(
+        "download-state",
+        DownloadStateArgs {
+            id: id.to_string(),
+            state: download_state,
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(start: &str, end: &str) -> ScheduleWindow {
+        ScheduleWindow {
+            start: start.into(),
+            end: end.into(),
+        }
+    }
+
+    fn at(time: &str) -> NaiveTime {
+        parse_time(time).unwrap()
+    }
+
+    #[test]
+    fn no_windows_is_always_open() {
+        assert!(in_window(&[], at("03:00")));
+    }
+
+    #[test]
+    fn window_within_a_day() {
+        let windows = [window("09:00", "17:00")];
+        assert!(!in_window(&windows, at("08:59")));
+        assert!(in_window(&windows, at("09:00")));
+        assert!(in_window(&windows, at("16:59")));
+        assert!(!in_window(&windows, at("17:00")));
+    }
+
+    #[test]
+    fn window_past_midnight() {
+        let windows = [window("22:00", "06:00")];
+        assert!(!in_window(&windows, at("21:59")));
+        assert!(in_window(&windows, at("22:00")));
+        assert!(in_window(&windows, at("00:00")));
+        assert!(in_window(&windows, at("05:59")));
+        assert!(!in_window(&windows, at("06:00")));
+        assert!(!in_window(&windows, at("12:00")));
+    }
+
+    #[test]
+    fn equal_bounds_are_open_all_day() {
+        let windows = [window("08:00", "08:00")];
+        assert!(in_window(&windows, at("07:59")));
+        assert!(in_window(&windows, at("08:00")));
+        assert!(in_window(&windows, at("23:59")));
+    }
+
+    #[test]
+    fn invalid_windows_never_open() {
+        let windows = [window("25:00", "06:00")];
+        assert!(!in_window(&windows, at("03:00")));
+        assert!(in_window(&[window("25:00", "06:00"), window("02:00", "04:00")], at("03:00")));
+    }
+
+    #[test]
+    fn rate_limits() {
+        for valid in ["500K", "2M", "1.5M", "1g", "1048576"] {
+            assert!(validate_rate_limit(valid).is_ok(), "{valid}");
+        }
+        for invalid in ["abc", "0", "0K", "-1M", "5KKK", "M", "", "NaN", "infK"] {
+            assert!(validate_rate_limit(invalid).is_err(), "{invalid}");
+        }
+    }
+}
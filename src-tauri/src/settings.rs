@@ -8,8 +8,11 @@ use std::{
 use tauri::{AppHandle, Manager, Runtime};
 use tauri_plugin_dialog::DialogExt;
 
-use crate::AppData;
-use yaydl_shared::{Settings, YaydlError};
+use crate::{library, post_process, schedule, sponsorblock, subtitles, ytdlp, AppData};
+use yaydl_shared::{
+    LoudnessSettings, NetworkSettings, NotificationSettings, PostProcessStep, QueueSort, ScheduleWindow, Settings, SponsorBlockSettings,
+    SubtitleOptions, YaydlError,
+};
 
 pub trait Setup {
     fn setup_settings(config_dir: &Path) -> Self;
@@ -39,6 +42,18 @@ impl Setup for Settings {
             output_dir: dirs::audio_dir().unwrap(),
             output_format: String::from("mp3"),
             dark_theme: true,
+            run_in_background: false,
+            notifications: Default::default(),
+            rate_limit: None,
+            schedule: Vec::new(),
+            network: Default::default(),
+            cookies: Default::default(),
+            binaries: Default::default(),
+            subtitles: Default::default(),
+            sponsorblock: Default::default(),
+            loudness: Default::default(),
+            post_processing: Vec::new(),
+            queue_sort: Default::default(),
         }
     }
 }
@@ -60,6 +75,7 @@ pub async fn choose_output_dir<R: Runtime>(
         .unwrap()
         .clone_into(&mut state.lock().unwrap().settings.output_dir);
     update_settings(&app_handle, &state);
+    library::watch(&app_handle);
 
     Ok(path.to_string())
 }
@@ -84,7 +100,120 @@ pub fn set_dark_theme<R: Runtime>(
     update_settings(&app_handle, &state)
 }
 
-fn update_settings<R: Runtime>(
+#[tauri::command]
+pub fn set_run_in_background<R: Runtime>(
+    value: bool,
+    app_handle: AppHandle<R>,
+    state: tauri::State<'_, Mutex<AppData>>,
+) -> bool {
+    state.lock().unwrap().settings.run_in_background = value;
+    update_settings(&app_handle, &state)
+}
+
+#[tauri::command]
+pub fn set_notification_settings<R: Runtime>(
+    value: NotificationSettings,
+    app_handle: AppHandle<R>,
+    state: tauri::State<'_, Mutex<AppData>>,
+) -> bool {
+    state.lock().unwrap().settings.notifications = value;
+    update_settings(&app_handle, &state)
+}
+
+#[tauri::command]
+pub fn set_subtitle_options<R: Runtime>(
+    value: SubtitleOptions,
+    app_handle: AppHandle<R>,
+    state: tauri::State<'_, Mutex<AppData>>,
+) -> Result<bool, YaydlError> {
+    subtitles::validate(&value)?;
+    state.lock().unwrap().settings.subtitles = value;
+    Ok(update_settings(&app_handle, &state))
+}
+
+#[tauri::command]
+pub fn set_sponsorblock_settings<R: Runtime>(
+    value: SponsorBlockSettings,
+    app_handle: AppHandle<R>,
+    state: tauri::State<'_, Mutex<AppData>>,
+) -> Result<bool, YaydlError> {
+    sponsorblock::validate(&value)?;
+    state.lock().unwrap().settings.sponsorblock = value;
+    Ok(update_settings(&app_handle, &state))
+}
+
+#[tauri::command]
+pub fn set_loudness_settings<R: Runtime>(
+    value: LoudnessSettings,
+    app_handle: AppHandle<R>,
+    state: tauri::State<'_, Mutex<AppData>>,
+) -> Result<bool, YaydlError> {
+    post_process::validate(&state.lock().unwrap().settings.post_processing, &value)?;
+    state.lock().unwrap().settings.loudness = value;
+    Ok(update_settings(&app_handle, &state))
+}
+
+#[tauri::command]
+pub fn set_post_processing<R: Runtime>(
+    value: Vec<PostProcessStep>,
+    app_handle: AppHandle<R>,
+    state: tauri::State<'_, Mutex<AppData>>,
+) -> Result<bool, YaydlError> {
+    post_process::validate(&value, &state.lock().unwrap().settings.loudness)?;
+    state.lock().unwrap().settings.post_processing = value;
+    Ok(update_settings(&app_handle, &state))
+}
+
+#[tauri::command]
+pub fn set_queue_sort<R: Runtime>(
+    value: QueueSort,
+    app_handle: AppHandle<R>,
+    state: tauri::State<'_, Mutex<AppData>>,
+) -> bool {
+    state.lock().unwrap().settings.queue_sort = value;
+    update_settings(&app_handle, &state)
+}
+
+#[tauri::command]
+pub fn set_rate_limit<R: Runtime>(
+    value: Option<String>,
+    app_handle: AppHandle<R>,
+    state: tauri::State<'_, Mutex<AppData>>,
+) -> Result<bool, YaydlError> {
+    let value = value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    if let Some(rate_limit) = &value {
+        schedule::validate_rate_limit(rate_limit)?;
+    }
+    state.lock().unwrap().settings.rate_limit = value;
+    Ok(update_settings(&app_handle, &state))
+}
+
+#[tauri::command]
+pub fn set_schedule<R: Runtime>(
+    value: Vec<ScheduleWindow>,
+    app_handle: AppHandle<R>,
+    state: tauri::State<'_, Mutex<AppData>>,
+) -> Result<bool, YaydlError> {
+    for window in &value {
+        schedule::parse_time(&window.start)?;
+        schedule::parse_time(&window.end)?;
+    }
+    state.lock().unwrap().settings.schedule = value;
+    Ok(update_settings(&app_handle, &state))
+}
+
+#[tauri::command]
+pub fn set_network_settings<R: Runtime>(
+    value: NetworkSettings,
+    app_handle: AppHandle<R>,
+    state: tauri::State<'_, Mutex<AppData>>,
+) -> Result<bool, YaydlError> {
+    ytdlp::validate_network_settings(&value)?;
+    state.lock().unwrap().settings.network = value;
+    Ok(update_settings(&app_handle, &state))
+}
+
+pub(crate) fn update_settings<R: Runtime>(
     app_handle: &AppHandle<R>,
     state: &tauri::State<'_, Mutex<AppData>>,
 ) -> bool {
@@ -0,0 +1,41 @@
+use url::Url;
+use yaydl_shared::{SettingsError, SponsorBlockSettings, SPONSORBLOCK_CATEGORIES};
+
+pub fn sponsorblock_args(settings: &SponsorBlockSettings) -> Vec<String> {
+    if !settings.enabled {
+        return Vec::new();
+    }
+    // Removing a segment wins over marking it
+    let mark: Vec<&str> = settings
+        .mark
+        .iter()
+        .filter(|c| !settings.remove.contains(c))
+        .map(String::as_str)
+        .collect();
+    let mut args = vec!["--sponsorblock-api".to_string(), settings.api_url.clone()];
+    if !settings.remove.is_empty() {
+        args.extend(["--sponsorblock-remove".to_string(), settings.remove.join(",")]);
+    }
+    if !mark.is_empty() {
+        args.extend(["--sponsorblock-mark".to_string(), mark.join(",")]);
+    }
+    args
+}
+
+pub fn validate(settings: &SponsorBlockSettings) -> Result<(), SettingsError> {
+    if let Some(category) = settings
+        .remove
+        .iter()
+        .chain(&settings.mark)
+        .find(|c| !SPONSORBLOCK_CATEGORIES.contains(&c.as_str()))
+    {
+        return Err(SettingsError::UnknownSponsorBlockCategory(category.clone()));
+    }
+    let valid = Url::parse(&settings.api_url)
+        .map(|url| ["http", "https"].contains(&url.scheme()) && url.host().is_some())
+        .unwrap_or(false);
+    if !valid {
+        return Err(SettingsError::InvalidSponsorBlockApi(settings.api_url.clone()));
+    }
+    Ok(())
+}
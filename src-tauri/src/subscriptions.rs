@@ -0,0 +1,364 @@
+use std::{fs, path::PathBuf, sync::Mutex, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use url::Url;
+use yaydl_shared::{
+    format_timestamp, Download, Metadata, Subscription, SubscriptionError, SubscriptionFilter,
+    YaydlError,
+};
+
+use crate::{now, printed, tray, ytdlp, AppData};
+
+const SUBSCRIPTIONS_FILE: &str = "subscriptions.toml";
+const POLL_INTERVAL: Duration = Duration::from_secs(30 * 60);
+/// Only the newest uploads are listed on every poll
+const PLAYLIST_END: &str = "50";
+/// Path prefixes of channels that aren't addressed by handle
+const CHANNEL_KINDS: [&str; 3] = ["channel", "c", "user"];
+
+#[derive(Serialize, Deserialize, Default)]
+struct SubscriptionsFile {
+    #[serde(default)]
+    subscriptions: Vec<Subscription>,
+}
+
+struct Entry {
+    id: String,
+    duration: Option<u32>,
+    playlist_title: String,
+    uploader: String,
+    title: String,
+}
+
+fn subscriptions_file<R: Runtime>(app_handle: &AppHandle<R>) -> PathBuf {
+    app_handle
+        .path()
+        .app_data_dir()
+        .unwrap()
+        .join(SUBSCRIPTIONS_FILE)
+}
+
+pub fn load<R: Runtime>(app_handle: &AppHandle<R>) -> Vec<Subscription> {
+    let Ok(content) = fs::read_to_string(subscriptions_file(app_handle)) else {
+        return Vec::new();
+    };
+    match toml::from_str::<SubscriptionsFile>(&content) {
+        Ok(file) => file.subscriptions,
+        Err(e) => {
+            println!("{}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn save<R: Runtime>(app_handle: &AppHandle<R>, subscriptions: &[Subscription]) -> Result<(), SubscriptionError> {
+    let path = subscriptions_file(app_handle);
+    let content = toml::to_string(&SubscriptionsFile {
+        subscriptions: subscriptions.to_vec(),
+    })
+    .map_err(|_| SubscriptionError::SaveFailed)?;
+    fs::create_dir_all(path.parent().unwrap()).map_err(|_| SubscriptionError::SaveFailed)?;
+    fs::write(path, content).map_err(|_| SubscriptionError::SaveFailed)
+}
+
+/// Channel URLs list their tabs instead of uploads unless they point at one.
+/// Playlists are kept as they are, tracking parameters are dropped from channels.
+fn normalize_url(url: &str) -> Result<String, SubscriptionError> {
+    let url = Url::parse(url.trim()).map_err(|_| SubscriptionError::InvalidUrl)?;
+    if url.scheme() != "https" || url.host_str() != Some("www.youtube.com") {
+        return Err(SubscriptionError::InvalidUrl);
+    }
+    if url.query_pairs().any(|(key, value)| key == "list" && !value.is_empty()) {
+        return Ok(url.to_string());
+    }
+    let segments: Vec<&str> = url
+        .path_segments()
+        .into_iter()
+        .flatten()
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    let path = match segments.as_slice() {
+        [handle] if handle.starts_with('@') => format!("{handle}/videos"),
+        [kind, name] if CHANNEL_KINDS.contains(kind) => format!("{kind}/{name}/videos"),
+        [handle, tab] if handle.starts_with('@') => format!("{handle}/{tab}"),
+        [kind, name, tab] if CHANNEL_KINDS.contains(kind) => format!("{kind}/{name}/{tab}"),
+        _ => return Err(SubscriptionError::InvalidUrl),
+    };
+    Ok(format!("https://www.youtube.com/{path}"))
+}
+
+async fn list_entries<R: Runtime>(app_handle: &AppHandle<R>, url: &str) -> Result<Vec<Entry>, YaydlError> {
+    let output = ytdlp::yt_dlp(app_handle)?
+        .args([
+            "--flat-playlist",
+            "--playlist-end",
+            PLAYLIST_END,
+            "--print",
+            "%(id)s\t%(duration)s\t%(playlist_title)s\t%(uploader,playlist_uploader)s\t%(title)s",
+            url,
+        ])
+        .output()
+        .await
+        .map_err(|e| YaydlError::TauriShellError(e.to_string()))?;
+    if !output.status.success() {
+        return Err(SubscriptionError::ListingFailed(url.into()).into());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(5, '\t');
+            Some(Entry {
+                id: fields.next()?.to_string(),
+                duration: fields.next()?.parse::<f32>().ok().map(|d| d as u32),
+                playlist_title: fields.next()?.to_string(),
+                uploader: printed(fields.next()?),
+                title: fields.next()?.to_string(),
+            })
+        })
+        .collect())
+}
+
+fn matches(filter: &SubscriptionFilter, entry: &Entry) -> bool {
+    let title = filter
+        .title_contains
+        .as_ref()
+        .is_none_or(|t| entry.title.to_lowercase().contains(&t.to_lowercase()));
+    let min = filter
+        .min_duration
+        .is_none_or(|min| entry.duration.is_some_and(|d| d >= min));
+    let max = filter
+        .max_duration
+        .is_none_or(|max| entry.duration.is_some_and(|d| d <= max));
+    title && min && max
+}
+
+/// Enqueues the new uploads of a subscription, returns how many were added.
+async fn check<R: Runtime>(app_handle: &AppHandle<R>, url: &str) -> Result<usize, YaydlError> {
+    let entries = list_entries(app_handle, url).await?;
+    let state = app_handle.state::<Mutex<AppData>>();
+    let mut state = state.lock().unwrap();
+    let AppData {
+        download_list,
+        subscriptions,
+        ..
+    } = &mut *state;
+    let Some(subscription) = subscriptions.iter_mut().find(|s| s.url == url) else {
+        return Ok(0);
+    };
+
+    let mut added = 0;
+    for entry in entries.iter().rev() {
+        if subscription.seen.contains(&entry.id) {
+            continue;
+        }
+        if !matches(&subscription.filter, entry) {
+            subscription.seen.push(entry.id.clone());
+            continue;
+        }
+        let download = Download {
+            metadata: Metadata {
+                id: entry.id.clone(),
+                url: format!("https://www.youtube.com/watch?v={}", entry.id),
+                title: entry.title.clone(),
+                duration: entry.duration.map(format_timestamp).unwrap_or_default(),
+                thumbnail: format!("https://i.ytimg.com/vi/{}/hqdefault.jpg", entry.id),
+                loading: false,
+                uploader: entry.uploader.clone(),
+                ..Default::default()
+            },
+            options: subscription.options.clone(),
+            subscription: Some(subscription.url.clone()),
+            added: now(),
+            ..Default::default()
+        };
+        if !download_list.contains(&download) {
+            download_list.insert(0, download);
+            added += 1;
+        }
+    }
+    let subscriptions = subscriptions.clone();
+    let downloads = (added > 0).then(|| download_list.clone());
+    // Written without holding the lock, the UI waits for it
+    drop(state);
+    save(app_handle, &subscriptions)?;
+    if let Some(downloads) = downloads {
+        let _ = app_handle.emit("downloads-changed", downloads);
+    }
+    tray::update_tray(app_handle);
+    Ok(added)
+}
+
+async fn check_all<R: Runtime>(app_handle: &AppHandle<R>) -> usize {
+    let urls: Vec<String> = app_handle
+        .state::<Mutex<AppData>>()
+        .lock()
+        .unwrap()
+        .subscriptions
+        .iter()
+        .filter(|s| s.enabled)
+        .map(|s| s.url.clone())
+        .collect();
+    let mut added = 0;
+    for url in urls {
+        match check(app_handle, &url).await {
+            Ok(count) => added += count,
+            Err(e) => println!("{}", e),
+        }
+    }
+    added
+}
+
+pub fn start_poller<R: Runtime>(app_handle: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            check_all(&app_handle).await;
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+}
+
+/// Remembers a finished download so the subscription never enqueues it again.
+pub fn mark_downloaded<R: Runtime>(app_handle: &AppHandle<R>, url: &str, id: &str) {
+    let subscriptions = {
+        let state = app_handle.state::<Mutex<AppData>>();
+        let mut state = state.lock().unwrap();
+        let Some(subscription) = state.subscriptions.iter_mut().find(|s| s.url == url) else {
+            return;
+        };
+        if !subscription.seen.iter().any(|s| s == id) {
+            subscription.seen.push(id.to_string());
+        }
+        state.subscriptions.clone()
+    };
+    if let Err(e) = save(app_handle, &subscriptions) {
+        println!("{}", e);
+    }
+}
+
+#[tauri::command]
+pub fn get_subscriptions(state: tauri::State<'_, Mutex<AppData>>) -> Vec<Subscription> {
+    state.lock().unwrap().subscriptions.clone()
+}
+
+/// Subscribes without downloading the existing uploads, only new ones are enqueued.
+#[tauri::command]
+pub async fn add_subscription<R: Runtime>(
+    mut value: Subscription,
+    app_handle: AppHandle<R>,
+    state: tauri::State<'_, Mutex<AppData>>,
+) -> Result<Vec<Subscription>, YaydlError> {
+    value.url = normalize_url(&value.url)?;
+    if state.lock().unwrap().subscriptions.iter().any(|s| s.url == value.url) {
+        return Err(SubscriptionError::AlreadySubscribed.into());
+    }
+    let entries = list_entries(&app_handle, &value.url).await?;
+    if value.name.trim().is_empty() {
+        value.name = entries
+            .first()
+            .map(|e| e.playlist_title.clone())
+            .unwrap_or_else(|| value.url.clone());
+    }
+    value.seen = entries.into_iter().map(|e| e.id).collect();
+
+    let subscriptions = {
+        let mut state = state.lock().unwrap();
+        state.subscriptions.push(value);
+        state.subscriptions.clone()
+    };
+    save(&app_handle, &subscriptions)?;
+    Ok(subscriptions)
+}
+
+/// Updates everything but the already seen uploads.
+#[tauri::command]
+pub fn update_subscription<R: Runtime>(
+    value: Subscription,
+    app_handle: AppHandle<R>,
+    state: tauri::State<'_, Mutex<AppData>>,
+) -> Result<Vec<Subscription>, YaydlError> {
+    let subscriptions = {
+        let mut state = state.lock().unwrap();
+        if let Some(subscription) = state.subscriptions.iter_mut().find(|s| s.url == value.url) {
+            let seen = std::mem::take(&mut subscription.seen);
+            *subscription = Subscription { seen, ..value };
+        }
+        state.subscriptions.clone()
+    };
+    save(&app_handle, &subscriptions)?;
+    Ok(subscriptions)
+}
+
+#[tauri::command]
+pub fn remove_subscription<R: Runtime>(
+    url: String,
+    app_handle: AppHandle<R>,
+    state: tauri::State<'_, Mutex<AppData>>,
+) -> Result<Vec<Subscription>, YaydlError> {
+    let subscriptions = {
+        let mut state = state.lock().unwrap();
+        state.subscriptions.retain(|s| s.url != url);
+        state.subscriptions.clone()
+    };
+    save(&app_handle, &subscriptions)?;
+    Ok(subscriptions)
+}
+
+#[tauri::command]
+pub async fn check_subscriptions<R: Runtime>(app_handle: AppHandle<R>) -> usize {
+    check_all(&app_handle).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalized(url: &str) -> Option<String> {
+        normalize_url(url).ok()
+    }
+
+    #[test]
+    fn channels_list_their_uploads() {
+        for (url, expected) in [
+            ("https://www.youtube.com/@handle", "https://www.youtube.com/@handle/videos"),
+            ("https://www.youtube.com/@handle/", "https://www.youtube.com/@handle/videos"),
+            ("https://www.youtube.com/@handle?si=abc#top", "https://www.youtube.com/@handle/videos"),
+            ("https://www.youtube.com/@handle/streams", "https://www.youtube.com/@handle/streams"),
+            ("https://www.youtube.com/channel/UC123", "https://www.youtube.com/channel/UC123/videos"),
+            ("https://www.youtube.com/c/Name/shorts", "https://www.youtube.com/c/Name/shorts"),
+            ("https://www.youtube.com/user/name", "https://www.youtube.com/user/name/videos"),
+            ("  https://www.youtube.com/@caf%C3%A9  ", "https://www.youtube.com/@caf%C3%A9/videos"),
+            ("https://www.youtube.com/@café", "https://www.youtube.com/@caf%C3%A9/videos"),
+        ] {
+            assert_eq!(normalized(url).as_deref(), Some(expected), "{url}");
+        }
+    }
+
+    #[test]
+    fn playlists_are_kept() {
+        let url = "https://www.youtube.com/playlist?list=PL123";
+        assert_eq!(normalized(url).as_deref(), Some(url));
+        let url = "https://www.youtube.com/watch?v=abc&list=PL123";
+        assert_eq!(normalized(url).as_deref(), Some(url));
+    }
+
+    #[test]
+    fn rejects_other_urls() {
+        for url in [
+            "",
+            "@handle",
+            "http://www.youtube.com/@handle",
+            "https://youtube.com.example.org/@handle",
+            "https://example.org/?u=https://www.youtube.com/@handle",
+            "https://www.youtube.com/",
+            "https://www.youtube.com/watch?v=abc",
+            "https://www.youtube.com/playlist?list=",
+            "https://www.youtube.com/channel",
+            "https://www.youtube.com/channel/UC123/videos/extra",
+        ] {
+            assert_eq!(normalized(url), None, "{url}");
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+use tauri::{AppHandle, Runtime};
+use yaydl_shared::{is_video_format, MetadataError, SettingsError, SubtitleFormat, SubtitleLanguages, SubtitleOptions, YaydlError};
+
+use crate::ytdlp;
+
+/// Auto-generated captions exist in every language YouTube translates to.
+pub fn validate(options: &SubtitleOptions) -> Result<(), SettingsError> {
+    if options.auto_generated && options.languages.is_empty() {
+        Err(SettingsError::MissingCaptionLanguages)
+    } else {
+        Ok(())
+    }
+}
+
+/// Embedding needs a video container, audio outputs always get sidecar files.
+pub fn subtitle_args(options: &SubtitleOptions, output_format: &str) -> Vec<String> {
+    if !options.manual && !options.auto_generated {
+        return Vec::new();
+    }
+    let mut args = Vec::new();
+    if options.manual {
+        args.push("--write-subs".to_string());
+    }
+    if options.auto_generated {
+        args.push("--write-auto-subs".into());
+    }
+    let languages = if options.languages.is_empty() {
+        "all".to_string()
+    } else {
+        options.languages.join(",")
+    };
+    let embed = options.embed && is_video_format(output_format);
+    // WebM only takes WebVTT subtitles
+    let format = if embed && output_format == "webm" {
+        SubtitleFormat::Vtt
+    } else {
+        options.format
+    };
+    args.extend([
+        "--sub-langs".into(),
+        languages,
+        "--convert-subs".into(),
+        format.extension().into(),
+    ]);
+    if embed {
+        args.push("--embed-subs".into());
+    }
+    args
+}
+
+fn languages(json: Option<&str>) -> Vec<String> {
+    let mut languages: Vec<String> = json
+        .and_then(|json| serde_json::from_str::<HashMap<String, serde_json::Value>>(json).ok())
+        .map(|subtitles| subtitles.into_keys().filter(|l| l != "live_chat").collect())
+        .unwrap_or_default();
+    languages.sort();
+    languages
+}
+
+#[tauri::command]
+pub async fn subtitle_languages<R: Runtime>(url: &str, app_handle: AppHandle<R>) -> Result<SubtitleLanguages, YaydlError> {
+    let output = ytdlp::yt_dlp(&app_handle)?
+        .args([
+            "--skip-download",
+            "--print",
+            "%(subtitles)j",
+            "--print",
+            "%(automatic_captions)j",
+            url,
+        ])
+        .output()
+        .await
+        .map_err(|e| YaydlError::TauriShellError(e.to_string()))?;
+    if !output.status.success() {
+        return Err(MetadataError::RetreivalFailed.into());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines();
+    Ok(SubtitleLanguages {
+        manual: languages(lines.next()),
+        auto_generated: languages(lines.next()),
+    })
+}
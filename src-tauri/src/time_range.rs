@@ -0,0 +1,58 @@
+use url::Url;
+use yaydl_shared::{TimeRange, YaydlError};
+
+/// Appended to the output template so every section gets its own file.
+pub const SECTION_SUFFIX: &str = " [%(section_start>%H.%M.%S)s-%(section_end>%H.%M.%S)s]";
+
+/// Seconds of a `t=` parameter like `90`, `90s` or `1h2m3s`.
+fn parse_start(value: &str) -> Option<u32> {
+    if let Ok(seconds) = value.parse() {
+        return Some(seconds);
+    }
+    let mut seconds = 0;
+    let mut number = String::new();
+    for c in value.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'h' | 'm' | 's' => {
+                let unit = match c {
+                    'h' => 3600,
+                    'm' => 60,
+                    _ => 1,
+                };
+                seconds += number.parse::<u32>().ok()? * unit;
+                number.clear();
+            }
+            _ => return None,
+        }
+    }
+    number.is_empty().then_some(seconds)
+}
+
+/// Range starting at the `t=` of a shared link, if it has one.
+pub fn from_url(url: &str) -> Option<TimeRange> {
+    let url = Url::parse(url).ok()?;
+    let (_, value) = url.query_pairs().find(|(key, _)| key == "t")?;
+    let start = parse_start(&value)?;
+    (start > 0).then_some(TimeRange { start, end: None })
+}
+
+pub fn validate(ranges: &[TimeRange]) -> Result<(), YaydlError> {
+    if ranges.iter().all(TimeRange::is_valid) {
+        Ok(())
+    } else {
+        Err(YaydlError::InvalidTimeRange)
+    }
+}
+
+pub fn section_args(ranges: &[TimeRange]) -> Vec<String> {
+    let mut args = Vec::new();
+    for range in ranges {
+        let end = range.end.map_or_else(|| "inf".to_string(), |end| end.to_string());
+        args.extend(["--download-sections".to_string(), format!("*{}-{end}", range.start)]);
+    }
+    if !args.is_empty() {
+        args.push("--force-keyframes-at-cuts".into());
+    }
+    args
+}
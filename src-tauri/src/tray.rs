@@ -0,0 +1,131 @@
+use std::sync::Mutex;
+
+use tauri::{
+    menu::{Menu, MenuItem, PredefinedMenuItem},
+    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
+    AppHandle, Emitter, Manager, Runtime,
+};
+use yaydl_shared::{Download, DownloadState};
+
+use crate::AppData;
+
+const TRAY_ID: &str = "main";
+
+/// Menu item showing the queue status, most Linux trays don't display tooltips.
+pub struct TrayStatus<R: Runtime>(MenuItem<R>);
+
+pub fn create_tray<R: Runtime>(app_handle: &AppHandle<R>) -> tauri::Result<()> {
+    let status = MenuItem::with_id(app_handle, "status", "Queue empty", false, None::<&str>)?;
+    let show = MenuItem::with_id(app_handle, "show", "Show yaydl", true, None::<&str>)?;
+    let add = MenuItem::with_id(app_handle, "add", "Add from clipboard", true, None::<&str>)?;
+    let start_all = MenuItem::with_id(app_handle, "start_all", "Start all", true, None::<&str>)?;
+    let stop_all = MenuItem::with_id(app_handle, "stop_all", "Stop all", true, None::<&str>)?;
+    let open_explorer =
+        MenuItem::with_id(app_handle, "open_explorer", "Open output folder", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app_handle, "quit", "Quit", true, None::<&str>)?;
+    let menu = Menu::with_items(
+        app_handle,
+        &[
+            &status,
+            &PredefinedMenuItem::separator(app_handle)?,
+            &show,
+            &add,
+            &start_all,
+            &stop_all,
+            &open_explorer,
+            &PredefinedMenuItem::separator(app_handle)?,
+            &quit,
+        ],
+    )?;
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&menu)
+        .tooltip("yaydl")
+        .show_menu_on_left_click(false)
+        .on_menu_event(|app_handle, event| match event.id.as_ref() {
+            "show" => show_window(app_handle),
+            "add" => {
+                let _ = app_handle.emit("tray-add", ());
+            }
+            "start_all" => {
+                let _ = app_handle.emit("tray-start-all", ());
+            }
+            "stop_all" => crate::stop_downloads(app_handle),
+            "open_explorer" => {
+                if let Err(e) =
+                    crate::open_explorer(app_handle.clone(), app_handle.state::<Mutex<AppData>>())
+                {
+                    println!("{}", e);
+                }
+            }
+            "quit" => app_handle.exit(0),
+            _ => {}
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                show_window(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app_handle.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app_handle)?;
+    app_handle.manage(TrayStatus(status));
+    Ok(())
+}
+
+pub fn show_window<R: Runtime>(app_handle: &AppHandle<R>) {
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// Refreshes the tray tooltip and status item from the current download list.
+pub fn update_tray<R: Runtime>(app_handle: &AppHandle<R>) {
+    let status = queue_status(&app_handle.state::<Mutex<AppData>>().lock().unwrap().download_list);
+    if let Some(tray) = app_handle.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(format!("yaydl - {status}")));
+    }
+    if let Some(item) = app_handle.try_state::<TrayStatus<R>>() {
+        let _ = item.0.set_text(&status);
+    }
+}
+
+fn queue_status(downloads: &[Download]) -> String {
+    if downloads.is_empty() {
+        return "Queue empty".into();
+    }
+    let total = downloads.len();
+    let mut finished = 0;
+    let mut active = false;
+    let mut progress = 0usize;
+    for download in downloads {
+        match download.download_state {
+            DownloadState::Finished => {
+                finished += 1;
+                progress += 100;
+            }
+            DownloadState::Loading(p) => {
+                active = true;
+                progress += p as usize;
+            }
+            DownloadState::PostProcessing(_) => {
+                active = true;
+                progress += 100;
+            }
+            _ => {}
+        }
+    }
+    if active {
+        format!("{finished}/{total} downloaded ({}%)", progress / total)
+    } else {
+        format!("{finished}/{total} downloaded")
+    }
+}
@@ -0,0 +1,174 @@
+use std::{fs, path::PathBuf, sync::Mutex};
+
+use serde::Deserialize;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_shell::ShellExt;
+use yaydl_shared::{Chapter, TimeRange, Tool, YaydlError};
+
+use crate::{binaries, AppData};
+
+/// Allowed difference to the expected duration in seconds, at least
+const MIN_TOLERANCE: f64 = 2.0;
+/// Allowed difference to the expected duration relative to it
+const RELATIVE_TOLERANCE: f64 = 0.02;
+/// Tags `-v level+...` puts on ffprobe messages that mean the file is broken
+const ERROR_LEVELS: [&str; 3] = ["[error]", "[fatal]", "[panic]"];
+
+#[derive(Deserialize)]
+struct Probe {
+    #[serde(default)]
+    streams: Vec<ProbeStream>,
+    format: Option<ProbeFormat>,
+}
+
+#[derive(Deserialize)]
+struct ProbeStream {
+    nb_read_frames: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ProbeFormat {
+    duration: Option<String>,
+}
+
+/// Expected duration of every output file in seconds, `None` where it is unknown.
+pub fn expected_durations(
+    total: Option<u32>,
+    time_ranges: &[TimeRange],
+    chapters: Option<&[Chapter]>,
+) -> Vec<Option<u32>> {
+    if let Some(chapters) = chapters {
+        chapters
+            .iter()
+            .map(|c| Some((c.end_time - c.start_time).max(0.0) as u32))
+            .collect()
+    } else if !time_ranges.is_empty() {
+        time_ranges.iter().map(|r| total.map(|t| r.length(t))).collect()
+    } else {
+        vec![total]
+    }
+}
+
+/// First error ffprobe printed, warnings like `Estimating duration from bitrate` don't count.
+fn probe_error(stderr: &str) -> Option<&str> {
+    stderr
+        .lines()
+        .map(str::trim)
+        .find(|line| ERROR_LEVELS.iter().any(|level| line.contains(level)))
+}
+
+fn within_tolerance(actual: f64, expected: u32) -> bool {
+    let expected = expected as f64;
+    (actual - expected).abs() <= MIN_TOLERANCE.max(expected * RELATIVE_TOLERANCE)
+}
+
+/// Checks that every file exists, decodes and is as long as expected.
+pub async fn verify<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    files: &[PathBuf],
+    expected: &[Option<u32>],
+) -> Result<(), YaydlError> {
+    if files.is_empty() {
+        return Err(YaydlError::VerificationFailed("No output file".into()));
+    }
+    let binaries = app_handle
+        .state::<Mutex<AppData>>()
+        .lock()
+        .unwrap()
+        .settings
+        .binaries
+        .clone();
+    let ffprobe = binaries::resolve(app_handle, Tool::Ffprobe, binaries::source(&binaries, Tool::Ffprobe))?;
+
+    for (index, file) in files.iter().enumerate() {
+        let name = file.file_name().unwrap_or_default().to_string_lossy();
+        let failed = |reason: &str| YaydlError::VerificationFailed(format!("{name}: {reason}"));
+        match fs::metadata(file) {
+            Ok(metadata) if metadata.len() > 0 => {}
+            Ok(_) => return Err(failed("file is empty")),
+            Err(_) => return Err(failed("file is missing")),
+        }
+
+        // Counting the frames decodes the whole stream
+        let output = app_handle
+            .shell()
+            .command(&ffprobe)
+            .args([
+                "-v",
+                "level+error",
+                "-count_frames",
+                "-select_streams",
+                "a:0",
+                "-show_entries",
+                "stream=nb_read_frames:format=duration",
+                "-of",
+                "json",
+            ])
+            .arg(file)
+            .output()
+            .await
+            .map_err(|e| YaydlError::TauriShellError(e.to_string()))?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if let Some(error) = probe_error(&stderr) {
+            return Err(failed(error));
+        }
+        let probe: Probe = serde_json::from_slice(&output.stdout).map_err(|_| failed("unreadable"))?;
+        let frames = probe
+            .streams
+            .first()
+            .and_then(|s| s.nb_read_frames.as_deref())
+            .and_then(|f| f.parse::<u64>().ok())
+            .unwrap_or(0);
+        if !output.status.success() || frames == 0 {
+            return Err(failed("no decodable audio"));
+        }
+
+        let duration = probe
+            .format
+            .and_then(|f| f.duration)
+            .and_then(|d| d.parse::<f64>().ok());
+        if let (Some(duration), Some(Some(expected))) = (duration, expected.get(index)) {
+            if !within_tolerance(duration, *expected) {
+                return Err(failed(&format!(
+                    "{duration:.0}s long instead of {expected}s",
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warnings_are_not_errors() {
+        let stderr = "[mp3 @ 0x55d0c8a3c2c0] [warning] Estimating duration from bitrate, this may be inaccurate\n\
+                      [mp3 @ 0x55d0c8a3c2c0] [warning] Discarding ID3 tags because more suitable tags were found.\n\
+                      [info] Input #0, mp3, from 'song.mp3':\n\n";
+        assert_eq!(probe_error(stderr), None);
+        assert_eq!(probe_error(""), None);
+    }
+
+    #[test]
+    fn errors_fail_verification() {
+        let stderr = "[mp3 @ 0x55d0c8a3c2c0] [warning] Estimating duration from bitrate, this may be inaccurate\n\
+                      [mp3float @ 0x55d0c8a3e040] [error] Header missing\n";
+        assert_eq!(probe_error(stderr), Some("[mp3float @ 0x55d0c8a3e040] [error] Header missing"));
+        assert_eq!(
+            probe_error("[fatal] song.m4a: Invalid data found when processing input\n"),
+            Some("[fatal] song.m4a: Invalid data found when processing input")
+        );
+    }
+
+    #[test]
+    fn durations_within_tolerance() {
+        assert!(within_tolerance(11.9, 10));
+        assert!(!within_tolerance(12.5, 10));
+        assert!(within_tolerance(196.5, 200));
+        assert!(!within_tolerance(195.0, 200));
+        assert!(within_tolerance(3580.0, 3600));
+        assert!(!within_tolerance(3500.0, 3600));
+    }
+}
@@ -0,0 +1,106 @@
+use std::path::{Path, PathBuf};
+
+/// Keeps the video in `format` instead of extracting the audio.
+pub fn video_args(format: &str) -> Vec<String> {
+    vec![
+        "--merge-output-format".into(),
+        format.into(),
+        // Formats with video and audio in one file aren't merged
+        "--remux-video".into(),
+        format.into(),
+    ]
+}
+
+/// Follows the files of a video download through yt-dlp's output, the separately
+/// downloaded video and audio are replaced by the file they are merged into.
+pub fn track_output(outputs: &mut Vec<PathBuf>, line: &str) {
+    let line = line.trim_end();
+    if let Some(path) = line.strip_prefix("[download] Destination: ") {
+        outputs.push(PathBuf::from(path));
+    } else if let Some(path) = line
+        .strip_prefix("[download] ")
+        .and_then(|rest| rest.strip_suffix(" has already been downloaded"))
+    {
+        outputs.push(PathBuf::from(path));
+    } else if let Some(path) = line
+        .strip_prefix("[Merger] Merging formats into \"")
+        .and_then(|rest| rest.strip_suffix('"'))
+    {
+        let merged = PathBuf::from(path);
+        outputs.retain(|part| !is_part_of(part, &merged));
+        outputs.push(merged);
+    } else if let Some((_, path)) = line
+        .strip_prefix("[VideoRemuxer] Remuxing video from ")
+        .and_then(|rest| rest.split_once("; Destination: "))
+    {
+        let remuxed = PathBuf::from(path);
+        outputs.retain(|source| source.with_extension("") != remuxed.with_extension(""));
+        outputs.push(remuxed);
+    }
+}
+
+/// Only files in the target container are results, this drops subtitles and leftover parts.
+pub fn finished(mut outputs: Vec<PathBuf>, format: &str) -> Vec<PathBuf> {
+    outputs.retain(|path| {
+        path.extension()
+            .is_some_and(|extension| extension == format)
+    });
+    outputs.dedup();
+    outputs
+}
+
+/// Parts are named like the merged file with the format ID before the extension, e.g. `title.f137.mp4`.
+fn is_part_of(part: &Path, merged: &Path) -> bool {
+    let (Some(part), Some(merged)) = (part.file_stem(), merged.file_stem()) else {
+        return false;
+    };
+    part.to_string_lossy()
+        .strip_prefix(&*merged.to_string_lossy())
+        .is_some_and(|rest| rest.starts_with(".f"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(lines: &[&str]) -> Vec<PathBuf> {
+        let mut outputs = Vec::new();
+        for line in lines {
+            track_output(&mut outputs, line);
+        }
+        outputs
+    }
+
+    #[test]
+    fn merged_file_replaces_parts() {
+        let outputs = track(&[
+            "[download] Destination: /music/A title.f137.mp4",
+            "[download]  42.0% of 10.00MiB at 1.00MiB/s ETA 00:05",
+            "[download] Destination: /music/A title.f251.webm",
+            "[Merger] Merging formats into \"/music/A title.mkv\"",
+        ]);
+        assert_eq!(outputs, vec![PathBuf::from("/music/A title.mkv")]);
+    }
+
+    #[test]
+    fn remuxed_file_replaces_source() {
+        let outputs = track(&[
+            "[download] /music/v1.2.webm has already been downloaded",
+            "[VideoRemuxer] Remuxing video from webm to mp4; Destination: /music/v1.2.mp4",
+        ]);
+        assert_eq!(outputs, vec![PathBuf::from("/music/v1.2.mp4")]);
+    }
+
+    #[test]
+    fn subtitles_are_not_results() {
+        let outputs = track(&[
+            "[download] Destination: /music/Title.en.vtt",
+            "[download] Destination: /music/Title.mp4",
+            "[VideoRemuxer] Not remuxing media file \"/music/Title.mp4\"; already is in target format mp4",
+        ]);
+        assert_eq!(
+            finished(outputs, "mp4"),
+            vec![PathBuf::from("/music/Title.mp4")]
+        );
+    }
+}
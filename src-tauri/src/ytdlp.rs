@@ -0,0 +1,119 @@
+use std::{net::IpAddr, sync::Mutex, time::Duration};
+
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_shell::{process::Command, ShellExt};
+use tauri_plugin_updater::{UpdaterBuilder, UpdaterExt};
+use url::Url;
+use yaydl_shared::{BinarySource, IpVersion, NetworkSettings, SettingsError, Tool, YaydlError};
+
+use crate::{binaries, cookies, ytdlp_update, AppData};
+
+const PROXY_SCHEMES: [&str; 6] = ["http", "https", "socks4", "socks4a", "socks5", "socks5h"];
+const TEST_URL: &str = "https://www.youtube.com/watch?v=jNQXAC9IVRw";
+
+/// Builds a yt-dlp command with the options every invocation has to share.
+pub fn yt_dlp<R: Runtime>(app_handle: &AppHandle<R>) -> Result<Command, YaydlError> {
+    let settings = app_handle
+        .state::<Mutex<AppData>>()
+        .lock()
+        .unwrap()
+        .settings
+        .clone();
+    let binary_settings = &settings.binaries;
+    let managed = ytdlp_update::managed_binary(app_handle).exists();
+    let sidecar = binary_settings.yt_dlp == BinarySource::Bundled && !managed;
+    let command = if sidecar {
+        app_handle
+            .shell()
+            .sidecar("yt-dlp")
+            .map_err(|e| YaydlError::TauriShellError(e.to_string()))?
+    } else {
+        app_handle
+            .shell()
+            .command(binaries::resolve(app_handle, Tool::YtDlp, &binary_settings.yt_dlp)?)
+    };
+    // The bundled ffmpeg is only found next to the bundled yt-dlp
+    let command = if sidecar && binary_settings.ffmpeg == BinarySource::Bundled {
+        command
+    } else {
+        let ffmpeg = binaries::resolve(app_handle, Tool::Ffmpeg, &binary_settings.ffmpeg)?;
+        command.args(["--ffmpeg-location".into(), ffmpeg.display().to_string()])
+    };
+    Ok(command
+        .args(network_args(&settings.network))
+        .args(cookies::cookie_args(app_handle, &settings.cookies)))
+}
+
+pub fn updater_builder<R: Runtime>(app_handle: &AppHandle<R>) -> UpdaterBuilder {
+    let network = app_handle
+        .state::<Mutex<AppData>>()
+        .lock()
+        .unwrap()
+        .settings
+        .network
+        .clone();
+    let mut builder = app_handle.updater_builder();
+    if let Some(proxy) = network.proxy.as_deref().and_then(|p| Url::parse(p).ok()) {
+        builder = builder.proxy(proxy);
+    }
+    if let Some(timeout) = network.socket_timeout {
+        builder = builder.timeout(Duration::from_secs(timeout.into()));
+    }
+    builder
+}
+
+fn network_args(network: &NetworkSettings) -> Vec<String> {
+    let mut args = Vec::new();
+    if let Some(proxy) = &network.proxy {
+        args.extend(["--proxy".to_string(), proxy.clone()]);
+    }
+    match network.ip_version {
+        IpVersion::Any => {}
+        IpVersion::V4 => args.push("--force-ipv4".into()),
+        IpVersion::V6 => args.push("--force-ipv6".into()),
+    }
+    if let Some(source_address) = &network.source_address {
+        args.extend(["--source-address".to_string(), source_address.clone()]);
+    }
+    if let Some(timeout) = network.socket_timeout {
+        args.extend(["--socket-timeout".to_string(), timeout.to_string()]);
+    }
+    args
+}
+
+pub fn validate_network_settings(network: &NetworkSettings) -> Result<(), SettingsError> {
+    if let Some(proxy) = &network.proxy {
+        let valid = Url::parse(proxy)
+            .map(|url| PROXY_SCHEMES.contains(&url.scheme()) && url.host().is_some())
+            .unwrap_or(false);
+        if !valid {
+            return Err(SettingsError::InvalidProxy(proxy.clone()));
+        }
+    }
+    if let Some(source_address) = &network.source_address {
+        if source_address.parse::<IpAddr>().is_err() {
+            return Err(SettingsError::InvalidSourceAddress(source_address.clone()));
+        }
+    }
+    if network.socket_timeout == Some(0) {
+        return Err(SettingsError::InvalidSocketTimeout);
+    }
+    Ok(())
+}
+
+/// Resolves a known video with the current network settings.
+#[tauri::command]
+pub async fn test_connection<R: Runtime>(app_handle: AppHandle<R>) -> Result<(), YaydlError> {
+    let output = yt_dlp(&app_handle)?
+        .args(["--skip-download", "--get-id", TEST_URL])
+        .output()
+        .await
+        .map_err(|e| YaydlError::TauriShellError(e.to_string()))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr.lines().rev().find(|l| !l.trim().is_empty()).unwrap_or_default();
+        return Err(YaydlError::ConnectionTestFailed(reason.to_string()));
+    }
+    Ok(())
+}
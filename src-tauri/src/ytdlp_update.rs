@@ -0,0 +1,229 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Duration,
+};
+
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_shell::ShellExt;
+use yaydl_shared::{BinarySource, YaydlError, YtDlpUpdateError, YtDlpVersion};
+
+use crate::{ytdlp, AppData};
+
+const RELEASES_API: &str = "https://api.github.com/repos/yt-dlp/yt-dlp/releases/latest";
+const RELEASE_DOWNLOAD: &str = "https://github.com/yt-dlp/yt-dlp/releases/download";
+const CHECKSUMS: &str = "SHA2-256SUMS";
+const BINARY_NAME: &str = if cfg!(target_os = "windows") {
+    "yt-dlp.exe"
+} else {
+    "yt-dlp"
+};
+
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+}
+
+/// Name of the release asset for this platform.
+fn asset_name() -> Result<&'static str, YaydlError> {
+    if cfg!(target_os = "windows") {
+        Ok("yt-dlp.exe")
+    } else if cfg!(target_os = "macos") {
+        Ok("yt-dlp_macos")
+    } else if cfg!(all(target_os = "linux", target_arch = "aarch64")) {
+        Ok("yt-dlp_linux_aarch64")
+    } else if cfg!(target_os = "linux") {
+        Ok("yt-dlp_linux")
+    } else {
+        Err(YaydlError::UnsupportedOs)
+    }
+}
+
+fn bin_dir<R: Runtime>(app_handle: &AppHandle<R>) -> PathBuf {
+    app_handle.path().app_data_dir().unwrap().join("bin")
+}
+
+/// yt-dlp downloaded at runtime, preferred over the bundled sidecar when present.
+pub fn managed_binary<R: Runtime>(app_handle: &AppHandle<R>) -> PathBuf {
+    bin_dir(app_handle).join(BINARY_NAME)
+}
+
+fn previous_binary<R: Runtime>(app_handle: &AppHandle<R>) -> PathBuf {
+    bin_dir(app_handle).join(format!("previous-{BINARY_NAME}"))
+}
+
+/// Updates only apply to the bundled yt-dlp, not a system or custom binary.
+fn uses_bundled<R: Runtime>(app_handle: &AppHandle<R>) -> bool {
+    app_handle
+        .state::<Mutex<AppData>>()
+        .lock()
+        .unwrap()
+        .settings
+        .binaries
+        .yt_dlp
+        == BinarySource::Bundled
+}
+
+fn http_client<R: Runtime>(app_handle: &AppHandle<R>) -> Result<reqwest::Client, YaydlError> {
+    let network = app_handle
+        .state::<Mutex<AppData>>()
+        .lock()
+        .unwrap()
+        .settings
+        .network
+        .clone();
+    let mut builder = reqwest::Client::builder().user_agent("yaydl");
+    if let Some(proxy) = network.proxy.as_deref().and_then(|p| reqwest::Proxy::all(p).ok()) {
+        builder = builder.proxy(proxy);
+    }
+    if let Some(timeout) = network.socket_timeout {
+        builder = builder.connect_timeout(Duration::from_secs(timeout.into()));
+    }
+    builder
+        .build()
+        .map_err(|_| YtDlpUpdateError::VersionCheckFailed.into())
+}
+
+async fn latest_version(client: &reqwest::Client) -> Result<String, YaydlError> {
+    let release: Release = client
+        .get(RELEASES_API)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|_| YtDlpUpdateError::VersionCheckFailed)?
+        .json()
+        .await
+        .map_err(|_| YtDlpUpdateError::VersionCheckFailed)?;
+    Ok(release.tag_name)
+}
+
+async fn download(client: &reqwest::Client, url: &str) -> Result<Vec<u8>, YaydlError> {
+    let bytes = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|_| YtDlpUpdateError::DownloadFailed)?
+        .bytes()
+        .await
+        .map_err(|_| YtDlpUpdateError::DownloadFailed)?;
+    Ok(bytes.to_vec())
+}
+
+async fn binary_version<R: Runtime>(app_handle: &AppHandle<R>, binary: &Path) -> Option<String> {
+    let output = app_handle
+        .shell()
+        .command(binary)
+        .arg("--version")
+        .output()
+        .await
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+async fn installed_version<R: Runtime>(app_handle: &AppHandle<R>) -> Result<String, YaydlError> {
+    let output = ytdlp::yt_dlp(app_handle)?
+        .arg("--version")
+        .output()
+        .await
+        .map_err(|e| YaydlError::TauriShellError(e.to_string()))?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn install(binary: &[u8], target: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(target.parent().unwrap())?;
+    fs::write(target, binary)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(target, fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(())
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+#[tauri::command]
+pub async fn yt_dlp_version<R: Runtime>(app_handle: AppHandle<R>) -> Result<YtDlpVersion, YaydlError> {
+    let installed = installed_version(&app_handle).await?;
+    let latest = match http_client(&app_handle) {
+        Ok(client) => latest_version(&client).await.ok(),
+        Err(_) => None,
+    };
+    Ok(YtDlpVersion {
+        installed,
+        latest,
+        managed: managed_binary(&app_handle).exists() && uses_bundled(&app_handle),
+    })
+}
+
+/// Downloads the latest yt-dlp, verifies it against the release checksums and
+/// rolls back to the previous binary if it fails to run. Nothing is touched if
+/// the latest version is installed, it would replace the rollback target with itself.
+#[tauri::command]
+pub async fn update_yt_dlp<R: Runtime>(app_handle: AppHandle<R>) -> Result<String, YaydlError> {
+    if !uses_bundled(&app_handle) {
+        return Err(YtDlpUpdateError::NotBundled.into());
+    }
+    let client = http_client(&app_handle)?;
+    let latest = latest_version(&client).await?;
+    if installed_version(&app_handle).await? == latest {
+        return Err(YtDlpUpdateError::AlreadyUpToDate(latest).into());
+    }
+    let asset = asset_name()?;
+
+    let binary = download(&client, &format!("{RELEASE_DOWNLOAD}/{latest}/{asset}")).await?;
+    let checksums = download(&client, &format!("{RELEASE_DOWNLOAD}/{latest}/{CHECKSUMS}")).await?;
+    let checksums = String::from_utf8_lossy(&checksums);
+    let expected = checksums
+        .lines()
+        .filter_map(|line| line.split_once(char::is_whitespace))
+        .find(|(_, name)| name.trim() == asset)
+        .map(|(hash, _)| hash.to_lowercase())
+        .ok_or(YtDlpUpdateError::ChecksumMismatch)?;
+    if sha256_hex(&binary) != expected {
+        return Err(YtDlpUpdateError::ChecksumMismatch.into());
+    }
+
+    let managed = managed_binary(&app_handle);
+    let previous = previous_binary(&app_handle);
+    if managed.exists() {
+        fs::rename(&managed, &previous).map_err(|_| YtDlpUpdateError::InstallFailed)?;
+    }
+    install(&binary, &managed).map_err(|_| YtDlpUpdateError::InstallFailed)?;
+
+    if binary_version(&app_handle, &managed).await.as_deref() != Some(latest.as_str()) {
+        let _ = fs::remove_file(&managed);
+        if previous.exists() {
+            let _ = fs::rename(&previous, &managed);
+        }
+        return Err(YtDlpUpdateError::SelfTestFailed.into());
+    }
+    Ok(latest)
+}
+
+/// Restores the previously downloaded yt-dlp, or the bundled sidecar if there is none.
+#[tauri::command]
+pub async fn rollback_yt_dlp<R: Runtime>(app_handle: AppHandle<R>) -> Result<String, YaydlError> {
+    let managed = managed_binary(&app_handle);
+    let previous = previous_binary(&app_handle);
+    if previous.exists() {
+        fs::rename(&previous, &managed).map_err(|_| YtDlpUpdateError::InstallFailed)?;
+    } else if managed.exists() {
+        fs::remove_file(&managed).map_err(|_| YtDlpUpdateError::InstallFailed)?;
+    } else {
+        return Err(YtDlpUpdateError::NoRollback.into());
+    }
+    installed_version(&app_handle).await
+}
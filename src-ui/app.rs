@@ -2,6 +2,7 @@ use crate::notification::{
     provide_notification_context, Notification, NotificationContext, NotificationList,
     NotificationType,
 };
+use crate::cookie_modal::{provide_cookie_prompt, CookieModal, CookiePrompt};
 use crate::update_modal::UpdateModal;
 use crate::update_context::provide_update_context;
 use leptos::*;
@@ -10,7 +11,7 @@ use serde::Deserialize;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::spawn_local;
 use yaydl_shared::{
-    AddLinkError, Download, DownloadEvent, DownloadState, DownloadStateArgs, Metadata, MetadataArgs, Settings, YaydlError
+    AddLinkError, BinarySettings, BinarySource, Chapter, CookieSource, Diagnostics, Download, DownloadEvent, DownloadOptionsArgs, DownloadState, DownloadStateArgs, FormatArgs, IdArgs, IdsArgs, ImportReport, IpVersion, LaunchArgs, LibraryItem, LibraryTags, MoveArgs, LoudnessMode, LoudnessSettings, Metadata, MetadataArgs, NetworkSettings, NotificationSettings, OffsetArgs, PathArgs, Playback, PriorityArgs, QueueFileArgs, QueueFileFormat, QueueSort, RetagArgs, PostProcessStep, ScheduleWindow, Settings, SortKey, StateFilter, SponsorBlockSettings, Subscription, SubtitleFormat, TimeRange, SubtitleLanguages, SubtitleOptions, Tool, ToolArgs, UrlArgs, ValueArgs, YaydlError, YtDlpUpdateError, YtDlpVersion, AUDIO_FORMATS, VIDEO_FORMATS, COOKIE_BROWSERS, SPONSORBLOCK_API, SPONSORBLOCK_CATEGORIES, format_timestamp, parse_timestamp
 };
 #[wasm_bindgen]
 extern "C" {
@@ -31,10 +32,23 @@ struct Event {
     event: String,
     payload: EventType,
 }
+/// Payload of the `library` event, sent whenever the output directory changes.
+#[derive(Debug, Deserialize)]
+struct LibraryEvent {
+    payload: Vec<LibraryItem>,
+}
+/// Payload of the `playback` event, `None` once playback stopped.
+#[derive(Debug, Deserialize)]
+struct PlaybackEvent {
+    payload: Option<Playback>,
+}
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
 enum EventType {
+    Downloads(Vec<Download>),
     Download(DownloadEvent),
+    State(DownloadStateArgs),
+    Launch(LaunchArgs),
     #[allow(dead_code)]
     SomethingOtherEvent,
 }
@@ -54,6 +68,22 @@ where
                         <Icon icon=icondata::FaDownloadSolid style="color: black" class="h-8 w-8"/>
                     </a>
                 </div>
+                <div
+                    class="h-14 w-14 hover:bg-blue-200 flex flex-col items-center justify-center"
+                    on:click=move |_| set_main_state(MainState::Subscriptions)
+                >
+                    <a href="#">
+                        <Icon icon=icondata::AiBellFilled style="color: black" class="h-8 w-8"/>
+                    </a>
+                </div>
+                <div
+                    class="h-14 w-14 hover:bg-blue-200 flex flex-col items-center justify-center"
+                    on:click=move |_| set_main_state(MainState::Library)
+                >
+                    <a href="#">
+                        <Icon icon=icondata::LuLibrary style="color: black" class="h-8 w-8"/>
+                    </a>
+                </div>
                 // <div
                 //     class="h-14 w-14 hover:bg-blue-200 flex flex-col items-center justify-center"
                 //     on:click=move |_| set_main_state(MainState::Statistics)
@@ -64,6 +94,14 @@ where
                 // </div>
             </div>
             <div class="flex-grow"></div>
+            <div
+                class="h-14 w-14 hover:bg-blue-200 flex flex-col items-center justify-center"
+                on:click=move |_| set_main_state(MainState::About)
+            >
+                <a href="#">
+                    <Icon icon=icondata::AiInfoCircleFilled style="color: black" class="h-8 w-8"/>
+                </a>
+            </div>
             <div
                 class="h-14 w-14 hover:bg-blue-200 flex flex-col items-center justify-center"
                 on:click=move |_| set_main_state(MainState::Settings)
@@ -83,98 +121,447 @@ where
 #[derive(Clone)]
 pub enum MainState {
     Settings,
+    About,
+    Subscriptions,
+    Library,
     Download,
     #[allow(dead_code)]
     Statistics,
 }
+async fn finish_add<F>(
+    result: Result<JsValue, JsValue>,
+    downloads: RwSignal<Vec<Download>>,
+    update_download_state: F,
+    notification_context: NotificationContext,
+    cookie_prompt: CookiePrompt,
+) where
+    F: Fn(String, DownloadState) + Copy + 'static,
+{
+    match result {
+        Ok(url) => {
+            let (url, dls): (String, Vec<Download>) =
+                serde_wasm_bindgen::from_value(url.clone()).unwrap();
+            downloads.set(dls);
+            update_download_state(url.to_string(), DownloadState::MetadataLoading);
+            fetch_metadata(url, downloads, notification_context, cookie_prompt).await;
+        }
+        Err(err) => {
+            let err: YaydlError = serde_wasm_bindgen::from_value(err.clone()).unwrap();
+            let notification_type = if let YaydlError::AddLinkError(ref add_err) = err {
+                Some(match add_err {
+                    AddLinkError::AlreadyAdded => NotificationType::Info,
+                    AddLinkError::NoValidLink => NotificationType::Warning,
+                    AddLinkError::ClipboardRead => NotificationType::Error,
+                })
+            } else {
+                None
+            };
+            if let Some(notification) = notification_type {
+                notification_context.add_notification(Notification {
+                    text: err.to_string(),
+                    notification_type: notification,
+                });
+            }
+        }
+    }
+}
+async fn fetch_chapters(url: String, downloads: RwSignal<Vec<Download>>) {
+    let args = serde_wasm_bindgen::to_value(&UrlArgs { url: url.clone() }).unwrap();
+    if let Ok(js_val) = invoke("retreive_chapters", args).await {
+        let chapters: Vec<Chapter> = serde_wasm_bindgen::from_value(js_val).unwrap_or_default();
+        downloads.update(|dls| {
+            if let Some(download) = dls.iter_mut().find(|d| d.metadata.url == url) {
+                download.metadata.chapters = chapters;
+            }
+        });
+    }
+}
+async fn fetch_metadata(
+    url: String,
+    downloads: RwSignal<Vec<Download>>,
+    notification_context: NotificationContext,
+    cookie_prompt: CookiePrompt,
+) {
+    let args = serde_wasm_bindgen::to_value(&MetadataArgs { url: &url, id: "" }).unwrap();
+    match invoke("retreive_metadata", args).await {
+        Ok(js_val) => {
+            let metadata: Metadata = serde_wasm_bindgen::from_value(js_val).unwrap();
+            let mut updated_downloads = downloads.get_untracked().clone();
+            if let Some(download) = updated_downloads.iter_mut().find(|d| d.metadata.url == url) {
+                download.metadata = metadata;
+            }
+            downloads.set(updated_downloads);
+            fetch_chapters(url, downloads).await;
+        }
+        Err(js_val) => {
+            let err: YaydlError = serde_wasm_bindgen::from_value(js_val).unwrap();
+            if let YaydlError::AuthRequired = err {
+                cookie_prompt.url.set(Some(url));
+                return;
+            }
+            notification_context.add_notification(Notification {
+                text: err.to_string(),
+                notification_type: NotificationType::Error,
+            });
+        }
+    }
+}
+thread_local! {
+    /// Set while a `download_queue` runs, a second one would download the same items again
+    static QUEUE_RUNNING: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+/// Tells the user if a queue is already running.
+fn queue_busy(notification_context: &NotificationContext) -> bool {
+    let busy = QUEUE_RUNNING.get();
+    if busy {
+        notification_context.add_notification(Notification {
+            text: "The queue is already running".into(),
+            notification_type: NotificationType::Info,
+        });
+    }
+    busy
+}
+async fn download_queue<F>(
+    downloads: Vec<Download>,
+    update_download_state: F,
+    notification_context: NotificationContext,
+) where
+    F: Fn(String, DownloadState) + Copy + 'static,
+{
+    if queue_busy(&notification_context) {
+        return;
+    }
+    QUEUE_RUNNING.set(true);
+    run_queue(downloads, update_download_state, notification_context).await;
+    QUEUE_RUNNING.set(false);
+}
+async fn run_queue<F>(
+    downloads: Vec<Download>,
+    update_download_state: F,
+    notification_context: NotificationContext,
+) where
+    F: Fn(String, DownloadState) + Copy + 'static,
+{
+    let mut downloads = downloads;
+    // Stable, so the queue order is kept within both groups
+    downloads.sort_by_key(|d| !d.high_priority);
+    let ids: Vec<String> = downloads.iter().map(|d| d.metadata.id.clone()).collect();
+    invoke_with_args("mark_scheduled", serde_wasm_bindgen::to_value(&IdsArgs { ids: ids.clone() }).unwrap()).await;
+    for download in downloads {
+        update_download_state(download.metadata.id.clone(), DownloadState::Loading(0));
+        let args = serde_wasm_bindgen::to_value(&MetadataArgs {
+            url: &download.metadata.url,
+            id: &download.metadata.id,
+        })
+        .unwrap();
+        match invoke("execute_yt_dl", args).await {
+            Ok(_) => {
+                update_download_state(
+                    download.metadata.id.clone(),
+                    DownloadState::Finished,
+                );
+            }
+            Err(js_val) => {
+                let err: YaydlError = serde_wasm_bindgen::from_value(js_val).unwrap();
+                match err {
+                    YaydlError::DownloadCancelled => {
+                        update_download_state(download.metadata.id.clone(), DownloadState::Idle);
+                        return;
+                    }
+                    // Only this download is gone, the rest of the queue continues
+                    YaydlError::DownloadRemoved => continue,
+                    _ => {}
+                }
+                let state = if let YaydlError::VerificationFailed(_) = err {
+                    DownloadState::VerificationFailed
+                } else {
+                    DownloadState::Failure
+                };
+                notification_context.add_notification(Notification {
+                    text: err.to_string(),
+                    notification_type: NotificationType::Error,
+                });
+                update_download_state(download.metadata.id.clone(), state);
+            }
+        }
+    }
+    invoke_with_args("queue_finished", serde_wasm_bindgen::to_value(&IdsArgs { ids }).unwrap()).await;
+}
+async fn handle_launch_args<F>(
+    launch_args: LaunchArgs,
+    downloads: RwSignal<Vec<Download>>,
+    update_download_state: F,
+    notification_context: NotificationContext,
+    cookie_prompt: CookiePrompt,
+) where
+    F: Fn(String, DownloadState) + Copy + 'static,
+{
+    for error in launch_args.errors {
+        notification_context.add_notification(Notification {
+            text: error,
+            notification_type: NotificationType::Warning,
+        });
+    }
+    for link in &launch_args.links {
+        let args = serde_wasm_bindgen::to_value(link).unwrap();
+        let result = invoke("add_url", args).await;
+        finish_add(result, downloads, update_download_state, notification_context.clone(), cookie_prompt).await;
+    }
+    if launch_args.start {
+        download_queue(downloads.get_untracked(), update_download_state, notification_context).await;
+    }
+}
 #[component()]
 pub fn MainContent<F>(downloads: RwSignal<Vec<Download>>, update_download_state: F) -> impl IntoView
 where
     F: Fn(String, DownloadState) + Copy + 'static,
 {
+    let notification_context = use_context::<NotificationContext>().unwrap();
+    let cookie_prompt = use_context::<CookiePrompt>().unwrap();
+    let download_all_context = notification_context.clone();
     let add = move |_| {
+        let notification_context = notification_context.clone();
+        spawn_local(async move {
+            let result = invoke("try_add", JsValue::NULL).await;
+            finish_add(result, downloads, update_download_state, notification_context, cookie_prompt).await;
+        });
+    };
+    let clear = move |_| {
+        spawn_local(async move {
+            invoke_without_args("clear_downloads").await;
+            downloads.set(vec![]);
+        });
+    };
+    let download_all = move |_| {
+        let notification_context = download_all_context.clone();
         spawn_local(async move {
-            match invoke("try_add", JsValue::NULL).await {
-                Ok(url) => {
-                    let (url, dls): (String, Vec<Download>) =
-                        serde_wasm_bindgen::from_value(url.clone()).unwrap();
+            download_queue(downloads.get_untracked(), update_download_state, notification_context).await;
+        });
+    };
+    // Queue edits return the updated queue
+    let edit_queue = move |cmd: &'static str, args: JsValue| {
+        spawn_local(async move {
+            if let Ok(js_val) = invoke(cmd, args).await {
+                if let Ok(dls) = serde_wasm_bindgen::from_value::<Vec<Download>>(js_val) {
                     downloads.set(dls);
-                    let args =
-                        serde_wasm_bindgen::to_value(&MetadataArgs { url: &url, id: "" }).unwrap();
-                    update_download_state(url.to_string(), DownloadState::MetadataLoading);
-                    match invoke("retreive_metadata", args).await {
-                        Ok(js_val) => {
-                            let metadata: Metadata = serde_wasm_bindgen::from_value(js_val).unwrap();
-                            let mut updated_downloads = downloads.get_untracked().clone();
-                            let latest_download = &mut updated_downloads[0];
-                            latest_download.metadata = metadata;
-                            downloads.set(updated_downloads);
-                        }
-                        Err(js_val) => {
-                            let err: YaydlError = serde_wasm_bindgen::from_value(js_val).unwrap();
-                            let notification_context =
-                                use_context::<NotificationContext>().unwrap();
-                            notification_context.add_notification(Notification {
-                                text: err.to_string(),
-                                notification_type: NotificationType::Error,
-                            });
+                }
+            }
+        });
+    };
+    let move_to = move |id: String, index: usize| {
+        edit_queue("move_download", serde_wasm_bindgen::to_value(&MoveArgs { id, index }).unwrap());
+    };
+    let dragged = create_rw_signal(None::<String>);
+    let search = create_rw_signal(String::new());
+    let state_filter = create_rw_signal(StateFilter::All);
+    let sort = create_rw_signal(QueueSort::default());
+    create_effect(move |_| {
+        spawn_local(async move {
+            if let Ok(js_val) = invoke("get_settings", JsValue::NULL).await {
+                let settings: Settings = serde_wasm_bindgen::from_value(js_val).unwrap();
+                sort.set(settings.queue_sort);
+            }
+        });
+    });
+    let set_sort = move |value: QueueSort| {
+        sort.set(value);
+        spawn_local(async move {
+            let _ = invoke("set_queue_sort", serde_wasm_bindgen::to_value(&ValueArgs { value }).unwrap()).await;
+        });
+    };
+    // Rows with their index in the queue, ties keep the queue order so progress doesn't move rows
+    let visible = move || {
+        let search = search.get().trim().to_lowercase();
+        let state_filter = state_filter.get();
+        let sort = sort.get();
+        let mut rows: Vec<(usize, Download)> = downloads
+            .get()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, d)| state_filter.matches(&d.download_state))
+            .filter(|(_, d)| {
+                search.is_empty()
+                    || [&d.metadata.title, &d.metadata.uploader, &d.metadata.url]
+                        .iter()
+                        .any(|field| field.to_lowercase().contains(&search))
+            })
+            .collect();
+        rows.sort_by(|(a_index, a), (b_index, b)| sort.compare(a, b).then(a_index.cmp(b_index)));
+        rows
+    };
+    // Moving rows only makes sense while all of them are shown in queue order
+    let reorderable = move || {
+        sort.get().key == SortKey::Queue && search.get().trim().is_empty() && state_filter.get() == StateFilter::All
+    };
+    let selected = create_rw_signal(Vec::<String>::new());
+    // Position of the last clicked row among the visible ones, shift-click selects up to it
+    let anchor = create_rw_signal(None::<usize>);
+    // Forgets removed downloads, also those removed with their own button
+    create_effect(move |_| {
+        let downloads = downloads.get();
+        let gone = |id: &String| !downloads.iter().any(|d| d.metadata.id == *id);
+        if selected.get_untracked().iter().any(gone) {
+            selected.update(|s| s.retain(|id| !gone(id)));
+            anchor.set(None);
+        }
+    });
+    let select = move |position: usize, id: String, range: bool| {
+        match anchor.get_untracked() {
+            Some(from) if range => {
+                let (start, end) = (from.min(position), from.max(position));
+                let ids: Vec<String> = untrack(visible)
+                    .iter()
+                    .skip(start)
+                    .take(end - start + 1)
+                    .map(|(_, d)| d.metadata.id.clone())
+                    .collect();
+                selected.update(|s| {
+                    for id in ids {
+                        if !s.contains(&id) {
+                            s.push(id);
                         }
                     }
-                }
-                Err(err) => {
-                    let err: YaydlError = serde_wasm_bindgen::from_value(err.clone()).unwrap();
-                    let notification_context = use_context::<NotificationContext>().unwrap();
-                    let notification_type = if let YaydlError::AddLinkError(ref add_err) = err {
-                        Some(match add_err {
-                            AddLinkError::AlreadyAdded => NotificationType::Info,
-                            AddLinkError::NoValidLink => NotificationType::Warning,
-                            AddLinkError::ClipboardRead => NotificationType::Error,
-                        })
+                });
+            }
+            _ => {
+                selected.update(|s| {
+                    if let Some(position) = s.iter().position(|i| *i == id) {
+                        s.remove(position);
                     } else {
-                        None
-                    };
-                    if let Some(notification) = notification_type {
-                        notification_context.add_notification(Notification {
-                            text: err.to_string(),
-                            notification_type: notification,
-                        });
+                        s.push(id);
                     }
-                }
+                });
+                anchor.set(Some(position));
             }
+        }
+    };
+    let select_where = move |f: fn(&Download) -> bool| {
+        selected.set(untrack(visible).iter().filter(|(_, d)| f(d)).map(|(_, d)| d.metadata.id.clone()).collect());
+        anchor.set(None);
+    };
+    let is_failed = |d: &Download| matches!(d.download_state, DownloadState::Failure | DownloadState::VerificationFailed);
+    // Selected downloads in queue order
+    let selection = move || {
+        let selected = selected.get_untracked();
+        downloads
+            .get_untracked()
+            .into_iter()
+            .filter(|d| selected.contains(&d.metadata.id))
+            .collect::<Vec<_>>()
+    };
+    let ids_args = move || serde_wasm_bindgen::to_value(&IdsArgs { ids: selected.get_untracked() }).unwrap();
+    let download_selected = move |_| {
+        let selection = selection();
+        spawn_local(async move {
+            let notification_context = use_context::<NotificationContext>().unwrap();
+            download_queue(selection, update_download_state, notification_context).await;
         });
     };
-    let clear = move |_| {
+    let remove_selected = move |_| {
+        edit_queue("remove_downloads", ids_args());
+        selected.set(Vec::new());
+    };
+    let format_selected = move |ev| {
+        let format = Some(event_target_value(&ev)).filter(|f| !f.is_empty());
+        let args = FormatArgs { ids: selected.get_untracked(), format };
+        edit_queue("set_downloads_format", serde_wasm_bindgen::to_value(&args).unwrap());
+    };
+    let copy_selected = move |_| {
         spawn_local(async move {
-            invoke_without_args("clear_downloads").await;
-            downloads.set(vec![]);
+            let notification_context = use_context::<NotificationContext>().unwrap();
+            let notification = match invoke("copy_urls", ids_args()).await {
+                Ok(js_val) => Notification {
+                    text: format!("Copied {} URL(s)", serde_wasm_bindgen::from_value::<usize>(js_val).unwrap_or(0)),
+                    notification_type: NotificationType::Success,
+                },
+                Err(err) => Notification {
+                    text: serde_wasm_bindgen::from_value::<YaydlError>(err).unwrap().to_string(),
+                    notification_type: NotificationType::Error,
+                },
+            };
+            notification_context.add_notification(notification);
         });
     };
-    let download_all = move |_| {
-        let downloads = downloads.get_untracked();
+    let retry_failed = move |_| {
+        let ids: Vec<String> = downloads
+            .get_untracked()
+            .iter()
+            .filter(|d| is_failed(d))
+            .map(|d| d.metadata.id.clone())
+            .collect();
         spawn_local(async move {
-            for download in downloads {
-                update_download_state(download.metadata.id.clone(), DownloadState::Loading(0));
-                let args = serde_wasm_bindgen::to_value(&MetadataArgs {
-                    url: &download.metadata.url,
-                    id: &download.metadata.id,
-                })
-                .unwrap();
-                match invoke("execute_yt_dl", args).await {
-                    Ok(_) => {
-                        update_download_state(
-                            download.metadata.id.clone(),
-                            DownloadState::Finished,
-                        );
-                    }
-                    Err(js_val) => {
-                        let err: YaydlError = serde_wasm_bindgen::from_value(js_val).unwrap();
-                        let notification_context = use_context::<NotificationContext>().unwrap();
+            // Checked before resetting, the failed downloads would be left idle
+            if queue_busy(&use_context::<NotificationContext>().unwrap()) {
+                return;
+            }
+            let args = serde_wasm_bindgen::to_value(&IdsArgs { ids: ids.clone() }).unwrap();
+            let Ok(js_val) = invoke("reset_downloads", args).await else {
+                return;
+            };
+            let dls: Vec<Download> = serde_wasm_bindgen::from_value(js_val).unwrap_or_default();
+            downloads.set(dls.clone());
+            let failed = dls.into_iter().filter(|d| ids.contains(&d.metadata.id)).collect();
+            let notification_context = use_context::<NotificationContext>().unwrap();
+            download_queue(failed, update_download_state, notification_context).await;
+        });
+    };
+    // Shows the placeholder again so picking the same format exports again
+    let reset_export = create_trigger();
+    let export_queue = move |ev| {
+        reset_export.notify();
+        let Some(format) = event_target_value(&ev).parse::<usize>().ok().and_then(|i| QueueFileFormat::ALL.get(i).copied()) else {
+            return;
+        };
+        spawn_local(async move {
+            let notification_context = use_context::<NotificationContext>().unwrap();
+            let args = serde_wasm_bindgen::to_value(&QueueFileArgs { format }).unwrap();
+            let notification = match invoke("export_queue", args).await {
+                Ok(js_val) => Notification {
+                    text: format!("Exported {} download(s)", serde_wasm_bindgen::from_value::<usize>(js_val).unwrap_or(0)),
+                    notification_type: NotificationType::Success,
+                },
+                Err(err) => match serde_wasm_bindgen::from_value::<YaydlError>(err).unwrap() {
+                    YaydlError::FileSelectionFailed => return,
+                    err => Notification {
+                        text: err.to_string(),
+                        notification_type: NotificationType::Error,
+                    },
+                },
+            };
+            notification_context.add_notification(notification);
+        });
+    };
+    let import_queue = move |_| {
+        spawn_local(async move {
+            let notification_context = use_context::<NotificationContext>().unwrap();
+            let (report, dls): (ImportReport, Vec<Download>) = match invoke("import_queue", JsValue::NULL).await {
+                Ok(js_val) => serde_wasm_bindgen::from_value(js_val).unwrap(),
+                Err(err) => {
+                    let err: YaydlError = serde_wasm_bindgen::from_value(err).unwrap();
+                    if !matches!(err, YaydlError::FileSelectionFailed) {
                         notification_context.add_notification(Notification {
                             text: err.to_string(),
                             notification_type: NotificationType::Error,
                         });
-                        update_download_state(download.metadata.id.clone(), DownloadState::Failure);
                     }
+                    return;
+                }
+            };
+            downloads.set(dls.clone());
+            let mut text = format!("Imported {} download(s)", report.added.len());
+            if !report.skipped.is_empty() {
+                let skipped: Vec<String> = report.skipped.iter().map(|s| format!("{} ({})", s.entry, s.reason)).collect();
+                text.push_str(&format!(", skipped {}: {}", skipped.len(), skipped.join("; ")));
+            }
+            notification_context.add_notification(Notification {
+                text,
+                notification_type: if report.skipped.is_empty() { NotificationType::Success } else { NotificationType::Warning },
+            });
+            // URL lists and CSV files may lack the metadata
+            for url in report.added {
+                if dls.iter().any(|d| d.metadata.url == url && d.metadata.id.is_empty()) {
+                    update_download_state(url.clone(), DownloadState::MetadataLoading);
+                    fetch_metadata(url, downloads, notification_context.clone(), cookie_prompt).await;
                 }
             }
         });
@@ -207,6 +594,20 @@ where
                 <Icon on:click=clear icon=icondata::AiClearOutlined class="h-8 w-8 fill-gray-500 hover:fill-gray-600"/>
             </button>
             <div class="flex-grow"></div>
+            <button on:click=import_queue title="Import queue" class="h-8 w-8">
+                <Icon icon=icondata::AiImportOutlined class="h-full w-full text-gray-500 hover:text-gray-600" />
+            </button>
+            <select class="p-1 rounded-md" title="Export queue" on:change=export_queue prop:value=move || {
+                reset_export.track();
+                ""
+            }>
+                <option value="" disabled selected>"Export"</option>
+                {QueueFileFormat::ALL
+                    .iter()
+                    .enumerate()
+                    .map(|(i, format)| view! { <option value=i>{format.name()}</option> })
+                    .collect_view()}
+            </select>
             <button on:click=open_explorer class="h-8 w-8">
                 <Icon icon=icondata::AiFolderOpenFilled class="h-full w-full text-gray-500 hover:text-gray-600" />
             </button>
@@ -214,13 +615,164 @@ where
                 <Icon on:click=download_all icon=icondata::LuDownload class="h-8 w-8 text-gray-500 hover:text-gray-600" />
             </button>
         </div>
+        <Show when=move || !downloads.get().is_empty()>
+            <div class="flex items-center h-10 px-2 bg-gray-200 space-x-2 text-sm">
+                <input
+                    type="search"
+                    class="p-1 rounded-md flex-1 min-w-0"
+                    placeholder="Search title, uploader or URL"
+                    prop:value=move || search.get()
+                    on:input=move |ev| search.set(event_target_value(&ev))
+                />
+                <select
+                    class="p-1 rounded-md"
+                    title="Show"
+                    on:change=move |ev| {
+                        if let Some(filter) = event_target_value(&ev).parse::<usize>().ok().and_then(|i| StateFilter::ALL.get(i)) {
+                            state_filter.set(*filter);
+                        }
+                    }
+                >
+                    {StateFilter::ALL
+                        .iter()
+                        .enumerate()
+                        .map(|(i, filter)| view! {
+                            <option value=i selected=move || state_filter.get() == *filter>{filter.name()}</option>
+                        })
+                        .collect_view()}
+                </select>
+                <select
+                    class="p-1 rounded-md"
+                    title="Sort by"
+                    on:change=move |ev| {
+                        if let Some(key) = event_target_value(&ev).parse::<usize>().ok().and_then(|i| SortKey::ALL.get(i)) {
+                            set_sort(QueueSort { key: *key, ..sort.get_untracked() });
+                        }
+                    }
+                >
+                    {SortKey::ALL
+                        .iter()
+                        .enumerate()
+                        .map(|(i, key)| view! {
+                            <option value=i selected=move || sort.get().key == *key>{key.name()}</option>
+                        })
+                        .collect_view()}
+                </select>
+                <button
+                    title=move || if sort.get().descending { "Descending" } else { "Ascending" }
+                    disabled=move || sort.get().key == SortKey::Queue
+                    on:click=move |_| {
+                        let sort = sort.get_untracked();
+                        set_sort(QueueSort { descending: !sort.descending, ..sort });
+                    }
+                >
+                    {move || if sort.get().descending {
+                        view! { <Icon icon=icondata::AiSortDescendingOutlined class="h-5 w-5 text-gray-500 hover:text-gray-700" /> }
+                    } else {
+                        view! { <Icon icon=icondata::AiSortAscendingOutlined class="h-5 w-5 text-gray-500 hover:text-gray-700" /> }
+                    }}
+                </button>
+            </div>
+            <div class="flex items-center h-10 px-2 bg-gray-200 space-x-2 text-sm">
+                <span>"Select"</span>
+                <button class="underline" on:click=move |_| select_where(|_| true)>"All"</button>
+                <button class="underline" on:click=move |_| select_where(is_failed)>"Failed"</button>
+                <button class="underline" on:click=move |_| select_where(|d| d.download_state == DownloadState::Finished)>"Finished"</button>
+                <button class="underline" on:click=move |_| select_where(|_| false)>"None"</button>
+                <Show when=move || downloads.get().iter().any(is_failed)>
+                    <button class="underline" on:click=retry_failed>"Retry failed"</button>
+                </Show>
+                <div class="flex-grow"></div>
+                <Show when=move || !selected.get().is_empty()>
+                    <span>{move || format!("{} selected", selected.get().len())}</span>
+                    <button class="underline" on:click=download_selected>"Download"</button>
+                    <button class="underline" on:click=remove_selected>"Remove"</button>
+                    <button class="underline" on:click=copy_selected>"Copy URLs"</button>
+                    <select class="p-1 rounded-md" on:change=format_selected>
+                        <option value="" disabled selected>"Change format"</option>
+                        {AUDIO_FORMATS.map(|f| view! { <option value=f>{f}</option> }).collect_view()}
+                        <optgroup label="Keep video">
+                            {VIDEO_FORMATS.map(|f| view! { <option value=f>{f}</option> }).collect_view()}
+                        </optgroup>
+                    </select>
+                </Show>
+            </div>
+        </Show>
         <div class="flex-1 p-[5px] overflow-auto">
+            <Show when=move || !downloads.get().is_empty() && visible().is_empty()>
+                <p class="p-2">"No downloads match the filter"</p>
+            </Show>
             <ul>
-                {move || downloads.get().into_iter()
-                    .map(|d| view! {
-                        <li>
-                            <Download d update_download_state />
-                        </li>
+                {move || visible().into_iter()
+                    .enumerate()
+                    .map(|(position, (index, d))| {
+                        let id = d.metadata.id.clone();
+                        let high_priority = d.high_priority;
+                        let drag_id = id.clone();
+                        let up_id = id.clone();
+                        let down_id = id.clone();
+                        let priority_id = id.clone();
+                        let select_id = id.clone();
+                        let checked_id = id.clone();
+                        view! {
+                            <li
+                                class="flex items-start space-x-1"
+                                draggable=move || if reorderable() { "true" } else { "false" }
+                                on:dragstart=move |_| dragged.set(Some(drag_id.clone()))
+                                on:dragend=move |_| dragged.set(None)
+                                on:dragover=move |ev| ev.prevent_default()
+                                on:drop=move |ev| {
+                                    ev.prevent_default();
+                                    if let Some(id) = dragged.get_untracked().filter(|_| reorderable()) {
+                                        move_to(id, index);
+                                    }
+                                    dragged.set(None);
+                                }
+                            >
+                                <input
+                                    type="checkbox"
+                                    class="mt-5"
+                                    title="Shift-click selects a range"
+                                    prop:checked=move || selected.get().contains(&checked_id)
+                                    on:click=move |ev| select(position, select_id.clone(), ev.shift_key())
+                                />
+                                <div class="flex flex-col pt-3">
+                                    <button title="Move up" disabled=move || !reorderable() on:click=move |_| move_to(up_id.clone(), index.saturating_sub(1))>
+                                        <Icon icon=icondata::AiUpOutlined class="h-4 w-4 text-gray-500 hover:text-gray-700" />
+                                    </button>
+                                    <button title="Move down" disabled=move || !reorderable() on:click=move |_| move_to(down_id.clone(), index + 1)>
+                                        <Icon icon=icondata::AiDownOutlined class="h-4 w-4 text-gray-500 hover:text-gray-700" />
+                                    </button>
+                                </div>
+                                <div class="flex-1 min-w-0">
+                                    <Download d update_download_state />
+                                </div>
+                                <div class="flex flex-col pt-3">
+                                    <button
+                                        title=if high_priority { "Remove high priority" } else { "Download first" }
+                                        on:click=move |_| {
+                                            let args = PriorityArgs { id: priority_id.clone(), high: !high_priority };
+                                            edit_queue("set_download_priority", serde_wasm_bindgen::to_value(&args).unwrap());
+                                        }
+                                    >
+                                        {if high_priority {
+                                            view! { <Icon icon=icondata::AiStarFilled class="h-4 w-4 text-yellow-500" /> }
+                                        } else {
+                                            view! { <Icon icon=icondata::AiStarOutlined class="h-4 w-4 text-gray-500 hover:text-gray-700" /> }
+                                        }}
+                                    </button>
+                                    <button
+                                        title="Remove"
+                                        on:click=move |_| {
+                                            let args = IdsArgs { ids: vec![id.clone()] };
+                                            edit_queue("remove_downloads", serde_wasm_bindgen::to_value(&args).unwrap());
+                                        }
+                                    >
+                                        <Icon icon=icondata::AiCloseOutlined class="h-4 w-4 text-gray-500 hover:text-gray-700" />
+                                    </button>
+                                </div>
+                            </li>
+                        }
                     })
                     .collect_view()
                 }
@@ -233,7 +785,11 @@ pub fn Download<F>(d: Download, update_download_state: F) -> impl IntoView
 where
     F: Fn(String, DownloadState) + Clone + Copy + 'static,
 {
-    let (download, _set_download) = create_signal(d);
+    let (download, set_download) = create_signal(d);
+    let show_options = create_rw_signal(false);
+    let (show_chapters, set_show_chapters) = create_signal(false);
+    let playback = use_context::<RwSignal<Option<Playback>>>().unwrap();
+    let own_playback = move || playback.get().filter(|p| p.id == download.get_untracked().metadata.id);
     let download_f = move |_| {
         let download_tmp = download.get().clone();
         update_download_state(download_tmp.metadata.id.clone(), DownloadState::Loading(0));
@@ -252,12 +808,26 @@ where
                 }
                 Err(js_val) => {
                     let err: YaydlError = serde_wasm_bindgen::from_value(js_val).unwrap();
+                    match err {
+                        YaydlError::DownloadCancelled => {
+                            update_download_state(download_tmp.metadata.id.clone(), DownloadState::Idle);
+                            return;
+                        }
+                        // Nothing left to update
+                        YaydlError::DownloadRemoved => return,
+                        _ => {}
+                    }
+                    let state = if let YaydlError::VerificationFailed(_) = err {
+                        DownloadState::VerificationFailed
+                    } else {
+                        DownloadState::Failure
+                    };
                     let notification_context = use_context::<NotificationContext>().unwrap();
                     notification_context.add_notification(Notification {
                         text: err.to_string(),
                         notification_type: NotificationType::Error,
                     });
-                    update_download_state(download_tmp.metadata.id.clone(), DownloadState::Failure);
+                    update_download_state(download_tmp.metadata.id.clone(), state);
                 }
             }
         });
@@ -285,12 +855,55 @@ where
                     />
                     <div class="w-full">
                         <p class="line-clamp-1">{&download.get_untracked().metadata.title}</p>
-                        <p class="text-sm">{&download.get_untracked().metadata.duration}</p>
+                        <p class="text-sm flex space-x-2">
+                            <span>{move || {
+                                let download = download.get();
+                                let duration = download.metadata.duration;
+                                let ranges = download.options.time_ranges;
+                                match parse_timestamp(&duration) {
+                                    Some(total) if !ranges.is_empty() => {
+                                        let selected = ranges.iter().map(|r| r.length(total)).sum();
+                                        format!("{} of {duration}", format_timestamp(selected))
+                                    }
+                                    _ => duration,
+                                }
+                            }}</span>
+                            <Show when=move || !download.get().metadata.uploader.is_empty()>
+                                <span class="line-clamp-1">{move || download.get().metadata.uploader}</span>
+                            </Show>
+                            <Show when=move || !download.get().metadata.chapters.is_empty()>
+                                <button class="underline" on:click=move |_| set_show_chapters.update(|s| *s = !*s)>
+                                    {move || {
+                                        let chapters = download.get().metadata.chapters.len();
+                                        let split = if download.get().options.split_chapters { ", split into tracks" } else { "" };
+                                        format!("{chapters} chapters{split}")
+                                    }}
+                                </button>
+                            </Show>
+                            {move || download.get().loudness.map(|l| view! {
+                                <span title=format!("True peak {:.1} dBTP, range {:.1} LU", l.true_peak, l.range)>
+                                    {format!("{:.1} LUFS", l.integrated)}
+                                </span>
+                            })}
+                        </p>
                     </div>
+                    {move || own_playback().map(|playback| view! { <PlaybackControls playback /> })}
                     {move || {
+                        let id = download.get_untracked().metadata.id;
                         match download.get_untracked().download_state {
                             DownloadState::Idle => {
                                 view! {
+                                    <Show when=move || own_playback().is_none()>
+                                        <button on:click={
+                                            let id = id.clone();
+                                            move |_| start_playback(id.clone(), "preview_download")
+                                        } class="h-8 w-8" title="Preview">
+                                            <Icon icon=icondata::AiSoundOutlined class="h-full w-full text-gray-600 hover:text-gray-800"/>
+                                        </button>
+                                    </Show>
+                                    <button on:click=move |_| show_options.set(true) class="h-8 w-8" title="Download options">
+                                        <Icon icon=icondata::AiSettingOutlined class="h-full w-full text-gray-600 hover:text-gray-800"/>
+                                    </button>
                                     <button on:click=download_f class="h-10 w-10">
                                         <Icon icon=icondata::BiDownloadSolid class="h-full w-full text-gray-600 hover:text-gray-800"/>
                                     </button>
@@ -332,6 +945,14 @@ where
                             }
                             DownloadState::Finished => {
                                 view! {
+                                    <Show when=move || own_playback().is_none()>
+                                        <button on:click={
+                                            let id = id.clone();
+                                            move |_| start_playback(id.clone(), "play_output")
+                                        } class="h-8 w-8" title="Play">
+                                            <Icon icon=icondata::AiPlayCircleOutlined class="h-full w-full text-gray-600 hover:text-gray-800"/>
+                                        </button>
+                                    </Show>
                                     <Icon icon=icondata::AiCheckCircleTwotone class="h-10 w-10 fill-green-600 stroke-green-600" style="stroke-width: 2%" />
                                }.into_view()
                             }
@@ -340,82 +961,1843 @@ where
                                     <Icon icon=icondata::BiErrorCircleRegular class="h-10 w-10 fill-red-600 stroke-red-600 stroke-[0.5px]" />
                                }.into_view()
                             }
+                            DownloadState::Scheduled => {
+                                view! {
+                                    <div title="Waiting for the next download window">
+                                        <Icon icon=icondata::AiClockCircleOutlined class="h-10 w-10 text-gray-600" />
+                                    </div>
+                               }.into_view()
+                            }
+                            DownloadState::VerificationFailed => {
+                                view! {
+                                    <div title="The downloaded file is missing, broken or incomplete">
+                                        <Icon icon=icondata::AiWarningOutlined class="h-10 w-10 text-orange-600" />
+                                    </div>
+                               }.into_view()
+                            }
+                            DownloadState::PostProcessing(step) => {
+                                view! {
+                                    <div class="flex items-center space-x-1 text-sm whitespace-nowrap" title="Post-processing">
+                                        <Icon icon=icondata::CgSpinner class="w-6 h-6 animate-spin text-gray-600" />
+                                        <span>{step}</span>
+                                    </div>
+                               }.into_view()
+                            }
                             _ => {}.into_view()
                         }
                     }}
                 }.into_view()
             }}
+            <DownloadOptionsModal download set_download show=show_options />
         </div>
+        <Show when=move || show_chapters.get()>
+            <ol class="list-decimal list-inside text-sm -mt-2 mb-2 px-2 py-1 rounded-b border-2 border-t-0 border-gray-400">
+                {move || download
+                    .get()
+                    .metadata
+                    .chapters
+                    .into_iter()
+                    .map(|c| view! { <li>{format!("{} ({})", c.title, format_timestamp(c.start_time as u32))}</li> })
+                    .collect_view()}
+            </ol>
+        </Show>
     }
 }
+/// Subtitle settings, `available` lists the languages of a specific video.
 #[component]
-pub fn Settings() -> impl IntoView {
-    let (output_dir, set_output_dir) = create_signal(String::new());
-    create_effect(move |_| {
-        spawn_local(async move {
-            if let Ok(js_val) = invoke("get_settings", JsValue::NULL).await {
-                let settings: Settings = serde_wasm_bindgen::from_value(js_val).unwrap();
-                set_output_dir.set(settings.output_dir.display().to_string());
-            }
-        });
-    });
-    let get_output_dir = move |_| {
-        spawn_local(async move {
-            if let Ok(js_val) = invoke("choose_output_dir", JsValue::NULL).await {
-                let notification_context = use_context::<NotificationContext>().unwrap();
-                let path = serde_wasm_bindgen::from_value(js_val).unwrap();
-                set_output_dir.set(path);
-                notification_context.add_notification(Notification {
-                    text: "Output directory updated successfully".into(),
-                    notification_type: NotificationType::Success,
-                });
+pub fn SubtitleOptionsEditor(
+    options: RwSignal<SubtitleOptions>,
+    #[prop(optional)] available: Option<RwSignal<Option<SubtitleLanguages>>>,
+) -> impl IntoView {
+    let toggle_language = move |language: String| {
+        options.update(|o| {
+            if let Some(index) = o.languages.iter().position(|l| *l == language) {
+                o.languages.remove(index);
+            } else {
+                o.languages.push(language);
             }
-        });
+        })
+    };
+    let language_list = move |languages: Vec<String>| {
+        languages
+            .into_iter()
+            .map(|language| {
+                let selected = {
+                    let language = language.clone();
+                    move || options.get().languages.contains(&language)
+                };
+                let label = language.clone();
+                view! {
+                    <button
+                        class="px-1 rounded-md text-sm bg-gray-300"
+                        class:bg-blue-300=selected
+                        on:click=move |_| toggle_language(language.clone())
+                    >
+                        {label}
+                    </button>
+                }
+            })
+            .collect_view()
     };
     view! {
-        <div class="flex items-center justify-center h-12 p-2 bg-gray-300">
-        </div>
-        <div class="flex flex-col h-full p-2">
-            <h2 class="text-xl">Settings</h2>
-            <span class="h-2 w-full border-2"></span>
-            <br />
-            <div class="flex space-x-1 items-center">
-                <button on:click=get_output_dir class="border-2 border-gray-500 h-8 w-52 rounded-md bg-gray-400 hover:bg-gray-500 shadow-md">
-                    "Set output directory"
-                </button>
-                <p class="bg-blue-300 p-1 rounded-md w-full">{output_dir}</p>
+        <div class="grid grid-cols-[10rem_1fr] gap-1 items-center">
+            <span>"Download"</span>
+            <div class="flex space-x-2">
+                <label class="flex space-x-1 items-center">
+                    <input
+                        type="checkbox"
+                        prop:checked=move || options.get().manual
+                        on:change=move |ev| options.update(|o| o.manual = event_target_checked(&ev))
+                    />
+                    <span>"Manual subtitles"</span>
+                </label>
+                <label class="flex space-x-1 items-center">
+                    <input
+                        type="checkbox"
+                        prop:checked=move || options.get().auto_generated
+                        on:change=move |ev| options.update(|o| o.auto_generated = event_target_checked(&ev))
+                    />
+                    <span>"Auto-generated captions"</span>
+                </label>
             </div>
+            <span>"Languages"</span>
+            <input
+                type="text"
+                placeholder="e.g. en, de, all if empty (manual only)"
+                class="p-1 rounded-md"
+                prop:value=move || options.get().languages.join(", ")
+                on:change=move |ev| {
+                    let languages = event_target_value(&ev)
+                        .split(',')
+                        .map(|l| l.trim().to_string())
+                        .filter(|l| !l.is_empty())
+                        .collect();
+                    options.update(|o| o.languages = languages);
+                }
+            />
+            {move || available.map(|available| match available.get() {
+                Some(languages) => view! {
+                    <span>"Available"</span>
+                    <div class="flex flex-wrap gap-1">{language_list(languages.manual)}</div>
+                    <span>"Auto-generated"</span>
+                    <div class="flex flex-wrap gap-1">{language_list(languages.auto_generated)}</div>
+                }.into_view(),
+                None => view! {
+                    <span>"Available"</span>
+                    <span class="text-sm">"Loading languages..."</span>
+                }.into_view(),
+            })}
+            <span>"Format"</span>
+            <select
+                class="p-1 rounded-md"
+                on:change=move |ev| {
+                    let value = event_target_value(&ev);
+                    if let Some(format) = SubtitleFormat::ALL.into_iter().find(|f| f.extension() == value) {
+                        options.update(|o| o.format = format);
+                    }
+                }
+            >
+                {SubtitleFormat::ALL
+                    .map(|f| view! {
+                        <option value=f.extension() selected=move || options.get().format == f>{f.extension()}</option>
+                    })
+                    .collect_view()}
+            </select>
+            <span>"Embed"</span>
+            <label class="flex space-x-1 items-center">
+                <input
+                    type="checkbox"
+                    prop:checked=move || options.get().embed
+                    on:change=move |ev| options.update(|o| o.embed = event_target_checked(&ev))
+                />
+                <span>"Embed into video formats (mkv, mp4, webm), audio formats get separate files"</span>
+            </label>
         </div>
     }
 }
+/// Per category choice of keeping, marking or removing SponsorBlock segments.
 #[component]
-pub fn Statistics() -> impl IntoView {
+pub fn SponsorBlockEditor(settings: RwSignal<SponsorBlockSettings>) -> impl IntoView {
+    let set_action = move |category: &'static str, action: String| {
+        settings.update(|s| {
+            s.remove.retain(|c| c != category);
+            s.mark.retain(|c| c != category);
+            match action.as_str() {
+                "remove" => s.remove.push(category.into()),
+                "mark" => s.mark.push(category.into()),
+                _ => {}
+            }
+        })
+    };
+    let action = move |category: &'static str| {
+        let settings = settings.get();
+        if settings.remove.iter().any(|c| c == category) {
+            "remove"
+        } else if settings.mark.iter().any(|c| c == category) {
+            "mark"
+        } else {
+            "keep"
+        }
+    };
     view! {
-        <div class="flex items-center justify-center h-12 p-2 bg-gray-300">
-            <p>Statistics buttons here</p>
-        </div>
-        <div class="flex w-full h-full p-2">
-            <p>Statistics here</p>
+        <label class="flex space-x-2 items-center">
+            <input
+                type="checkbox"
+                prop:checked=move || settings.get().enabled
+                on:change=move |ev| settings.update(|s| s.enabled = event_target_checked(&ev))
+            />
+            <span>"Use SponsorBlock"</span>
+        </label>
+        <Show when=move || settings.get().enabled>
+            <div class="grid grid-cols-[10rem_1fr] gap-1 items-center mt-1">
+                {SPONSORBLOCK_CATEGORIES
+                    .map(|category| view! {
+                        <span>{category}</span>
+                        <select class="p-1 rounded-md w-48" on:change=move |ev| set_action(category, event_target_value(&ev))>
+                            <option value="keep" selected=move || action(category) == "keep">"Keep"</option>
+                            <option value="mark" selected=move || action(category) == "mark">"Mark as chapter"</option>
+                            <option value="remove" selected=move || action(category) == "remove">"Remove"</option>
+                        </select>
+                    })
+                    .collect_view()}
+                <span>"API"</span>
+                <input
+                    type="text"
+                    class="p-1 rounded-md"
+                    placeholder=SPONSORBLOCK_API
+                    prop:value=move || settings.get().api_url
+                    on:change=move |ev| {
+                        let value = event_target_value(&ev);
+                        settings.update(|s| s.api_url = if value.is_empty() { SPONSORBLOCK_API.into() } else { value });
+                    }
+                />
+            </div>
+        </Show>
+    }
+}
+#[component]
+pub fn LoudnessEditor(settings: RwSignal<LoudnessSettings>) -> impl IntoView {
+    let set_mode = move |value: String| {
+        let mode = match value.as_str() {
+            "normalize" => LoudnessMode::Normalize,
+            "replaygain" => LoudnessMode::ReplayGain,
+            _ => LoudnessMode::Off,
+        };
+        settings.update(|s| s.mode = mode);
+    };
+    let set_number = move |value: String, f: fn(&mut LoudnessSettings, f32)| {
+        if let Ok(value) = value.parse() {
+            settings.update(|s| f(s, value));
+        }
+    };
+    view! {
+        <div class="grid grid-cols-[10rem_1fr] gap-1 items-center">
+            <span>"Mode"</span>
+            <select class="p-1 rounded-md w-64" on:change=move |ev| set_mode(event_target_value(&ev))>
+                <option value="off" selected=move || settings.get().mode == LoudnessMode::Off>"Off"</option>
+                <option value="normalize" selected=move || settings.get().mode == LoudnessMode::Normalize>"Normalize (re-encodes)"</option>
+                <option value="replaygain" selected=move || settings.get().mode == LoudnessMode::ReplayGain>"Write ReplayGain tags"</option>
+            </select>
+            <Show when=move || settings.get().mode == LoudnessMode::Normalize>
+                <span>"Target (LUFS)"</span>
+                <input
+                    type="number"
+                    step="0.5"
+                    class="p-1 rounded-md w-24"
+                    prop:value=move || settings.get().target
+                    on:change=move |ev| set_number(event_target_value(&ev), |s, v| s.target = v)
+                />
+                <span>"True peak (dBTP)"</span>
+                <input
+                    type="number"
+                    step="0.5"
+                    class="p-1 rounded-md w-24"
+                    prop:value=move || settings.get().true_peak
+                    on:change=move |ev| set_number(event_target_value(&ev), |s, v| s.true_peak = v)
+                />
+            </Show>
         </div>
     }
 }
+/// Ordered list of post-processing steps, each edited in place.
 #[component]
-pub fn App() -> impl IntoView {
-    let (state, set_state) = create_signal(MainState::Download);
-    let downloads = create_rw_signal(vec![]);
-    let _ = provide_notification_context();
-    let update_context = provide_update_context();
-    let update_context2 = update_context.clone();
-    let update_context3 = update_context.clone();
-    let (show_update, set_show_update) = create_signal(update_context.state.get().show);
-    let (update_progress, set_update_progress) = create_signal(update_context.state.get().progress);
-    create_effect(move |_| {
-        set_show_update.set(update_context.state.get().show);
-        set_update_progress.set(update_context.state.get().progress);
-    });
-    // Listen for update events from backend
-    create_effect(move |_| {
-        let update_context = update_context2.clone();
+pub fn PostProcessingEditor(steps: RwSignal<Vec<PostProcessStep>>) -> impl IntoView {
+    let (new_step, set_new_step) = create_signal("convert".to_string());
+    let add = move |_| {
+        let step = match new_step.get_untracked().as_str() {
+            "convert" => PostProcessStep::Convert { format: "flac".into() },
+            "trim" => PostProcessStep::TrimSilence,
+            "normalize" => PostProcessStep::Normalize(LoudnessSettings {
+                mode: LoudnessMode::Normalize,
+                ..Default::default()
+            }),
+            "tag" => PostProcessStep::Tag { tags: vec![("album".into(), "{title}".into())] },
+            "move" => PostProcessStep::Move { dir: String::new() },
+            _ => PostProcessStep::Command { program: String::new(), args: vec!["{path}".into()] },
+        };
+        steps.update(|s| s.push(step));
+    };
+    let update = move |index: usize, f: Box<dyn Fn(&mut PostProcessStep)>| {
+        steps.update(|s| {
+            if let Some(step) = s.get_mut(index) {
+                f(step);
+            }
+        })
+    };
+    let step_view = move |index: usize, step: PostProcessStep| {
+        let name = step.name();
+        let fields = match step {
+            PostProcessStep::Convert { format } => view! {
+                <select
+                    class="p-1 rounded-md w-32"
+                    on:change=move |ev| {
+                        let value = event_target_value(&ev);
+                        update(index, Box::new(move |s| *s = PostProcessStep::Convert { format: value.clone() }))
+                    }
+                >
+                    {AUDIO_FORMATS
+                        .iter()
+                        .filter(|f| **f != "best")
+                        .map(|f| view! { <option value=*f selected=*f == format>{*f}</option> })
+                        .collect_view()}
+                </select>
+            }.into_view(),
+            PostProcessStep::TrimSilence => view! { <span class="text-sm">"Start and end"</span> }.into_view(),
+            PostProcessStep::Normalize(settings) => {
+                let settings = create_rw_signal(settings);
+                create_effect(move |_| {
+                    let settings = settings.get();
+                    steps.update_untracked(|s| {
+                        if let Some(step) = s.get_mut(index) {
+                            *step = PostProcessStep::Normalize(settings);
+                        }
+                    });
+                });
+                view! { <LoudnessEditor settings /> }.into_view()
+            }
+            PostProcessStep::Tag { tags } => {
+                let text = tags.iter().map(|(k, v)| format!("{k}={v}")).collect::<Vec<_>>().join("\n");
+                view! {
+                    <textarea
+                        class="p-1 rounded-md w-96 h-16"
+                        placeholder="One key=value per line, e.g. album={title}"
+                        prop:value=text
+                        on:change=move |ev| {
+                            let tags: Vec<(String, String)> = event_target_value(&ev)
+                                .lines()
+                                .filter_map(|l| l.split_once('='))
+                                .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+                                .collect();
+                            update(index, Box::new(move |s| *s = PostProcessStep::Tag { tags: tags.clone() }))
+                        }
+                    ></textarea>
+                }.into_view()
+            }
+            PostProcessStep::Move { dir } => view! {
+                <input
+                    type="text"
+                    class="p-1 rounded-md w-96"
+                    placeholder="Directory, e.g. /music/{title}"
+                    prop:value=dir
+                    on:change=move |ev| {
+                        let dir = event_target_value(&ev);
+                        update(index, Box::new(move |s| *s = PostProcessStep::Move { dir: dir.clone() }))
+                    }
+                />
+            }.into_view(),
+            PostProcessStep::Command { program, args } => {
+                let (program, set_program) = create_signal(program);
+                let (args, set_args) = create_signal(args.join(" "));
+                let save = move || {
+                    let step = PostProcessStep::Command {
+                        program: program.get_untracked(),
+                        args: args.get_untracked().split_whitespace().map(String::from).collect(),
+                    };
+                    update(index, Box::new(move |s| *s = step.clone()))
+                };
+                view! {
+                    <input
+                        type="text"
+                        class="p-1 rounded-md w-48"
+                        placeholder="Program"
+                        prop:value=program
+                        on:change=move |ev| { set_program.set(event_target_value(&ev)); save() }
+                    />
+                    <input
+                        type="text"
+                        class="p-1 rounded-md w-64"
+                        placeholder="Arguments, e.g. {path}"
+                        prop:value=args
+                        on:change=move |ev| { set_args.set(event_target_value(&ev)); save() }
+                    />
+                }.into_view()
+            }
+        };
+        view! {
+            <li class="flex space-x-2 items-center">
+                <span class="w-24">{format!("{}. {name}", index + 1)}</span>
+                <div class="flex space-x-2 items-center">{fields}</div>
+                <button title="Move up" on:click=move |_| steps.update(|s| if index > 0 { s.swap(index, index - 1) })>
+                    <Icon icon=icondata::AiArrowUpOutlined class="h-5 w-5 text-gray-600 hover:text-gray-800" />
+                </button>
+                <button title="Remove" on:click=move |_| steps.update(|s| { s.remove(index); })>
+                    <Icon icon=icondata::AiDeleteOutlined class="h-5 w-5 text-gray-600 hover:text-gray-800" />
+                </button>
+            </li>
+        }
+    };
+    view! {
+        <p class="text-sm">"Placeholders: {path} {dir} {name} {ext} {title} {id} {url}"</p>
+        <ol class="space-y-1 mt-1">
+            {move || steps.get().into_iter().enumerate().map(|(index, step)| step_view(index, step)).collect_view()}
+        </ol>
+        <div class="flex space-x-2 items-center mt-1">
+            <select class="p-1 rounded-md w-48" on:change=move |ev| set_new_step.set(event_target_value(&ev))>
+                <option value="convert">"Convert"</option>
+                <option value="trim">"Trim silence"</option>
+                <option value="normalize">"Normalize"</option>
+                <option value="tag">"Tag"</option>
+                <option value="move">"Move"</option>
+                <option value="command">"Run command"</option>
+            </select>
+            <button class="border-2 border-gray-500 h-8 px-2 rounded-md bg-gray-400 hover:bg-gray-500 shadow-md" on:click=add>
+                "Add step"
+            </button>
+        </div>
+    }
+}
+/// Starts ffplay on a download, `command` is `preview_download` or `play_output`.
+fn start_playback(id: String, command: &'static str) {
+    spawn_local(async move {
+        let args = serde_wasm_bindgen::to_value(&IdArgs { id }).unwrap();
+        if let Err(err) = invoke(command, args).await {
+            let err: YaydlError = serde_wasm_bindgen::from_value(err).unwrap();
+            let notification_context = use_context::<NotificationContext>().unwrap();
+            notification_context.add_notification(Notification {
+                text: err.to_string(),
+                notification_type: NotificationType::Error,
+            });
+        }
+    });
+}
+/// Position and seek/stop buttons of the running playback.
+#[component]
+pub fn PlaybackControls(playback: Playback) -> impl IntoView {
+    let position = create_rw_signal(playback.position);
+    // ffplay has no way to report its position, so it is counted here
+    if let Ok(handle) = set_interval_with_handle(
+        move || position.update(|p| *p += 1),
+        std::time::Duration::from_secs(1),
+    ) {
+        on_cleanup(move || handle.clear());
+    }
+    let seek = move |offset: i32| {
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&OffsetArgs { offset }).unwrap();
+            let _ = invoke("seek_playback", args).await;
+        });
+    };
+    let stop = move |_| {
+        spawn_local(async move {
+            invoke_without_args("stop_playback").await;
+        });
+    };
+    let end = playback.end.map(|end| format!(" / {}", format_timestamp(end))).unwrap_or_default();
+    view! {
+        <div class="flex items-center space-x-1 text-sm whitespace-nowrap">
+            <button title="Back 10 seconds" on:click=move |_| seek(-10)>
+                <Icon icon=icondata::AiBackwardOutlined class="h-6 w-6 text-gray-600 hover:text-gray-800" />
+            </button>
+            <button title="Stop" on:click=stop>
+                <Icon icon=icondata::BsStopFill class="h-6 w-6 text-gray-600 hover:text-gray-800" />
+            </button>
+            <button title="Forward 10 seconds" on:click=move |_| seek(10)>
+                <Icon icon=icondata::AiForwardOutlined class="h-6 w-6 text-gray-600 hover:text-gray-800" />
+            </button>
+            <span>{move || format!("{}{end}", format_timestamp(position.get()))}</span>
+        </div>
+    }
+}
+/// Per-download overrides of the defaults from the settings.
+#[component]
+pub fn DownloadOptionsModal(
+    download: ReadSignal<Download>,
+    set_download: WriteSignal<Download>,
+    show: RwSignal<bool>,
+) -> impl IntoView {
+    let options = create_rw_signal(download.get_untracked().options);
+    let subtitles = create_rw_signal(options.get_untracked().subtitles.unwrap_or_default());
+    let (custom_subtitles, set_custom_subtitles) = create_signal(options.get_untracked().subtitles.is_some());
+    let sponsorblock = create_rw_signal(options.get_untracked().sponsorblock.unwrap_or_default());
+    let (custom_sponsorblock, set_custom_sponsorblock) = create_signal(options.get_untracked().sponsorblock.is_some());
+    let loudness = create_rw_signal(options.get_untracked().loudness.unwrap_or_default());
+    let (custom_loudness, set_custom_loudness) = create_signal(options.get_untracked().loudness.is_some());
+    // Start from the global settings when overriding them
+    create_effect(move |_| {
+        if !show.get() {
+            return;
+        }
+        spawn_local(async move {
+            if let Ok(js_val) = invoke("get_settings", JsValue::NULL).await {
+                let settings: Settings = serde_wasm_bindgen::from_value(js_val).unwrap();
+                if !custom_sponsorblock.get_untracked() {
+                    sponsorblock.set(settings.sponsorblock);
+                }
+                if !custom_loudness.get_untracked() {
+                    loudness.set(settings.loudness);
+                }
+            }
+        });
+    });
+    let available = create_rw_signal(None::<SubtitleLanguages>);
+    create_effect(move |_| {
+        if !show.get() || available.get_untracked().is_some() {
+            return;
+        }
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&UrlArgs {
+                url: download.get_untracked().metadata.url,
+            })
+            .unwrap();
+            let languages = match invoke("subtitle_languages", args).await {
+                Ok(js_val) => serde_wasm_bindgen::from_value(js_val).unwrap_or_default(),
+                Err(_) => SubtitleLanguages::default(),
+            };
+            available.set(Some(languages));
+        });
+    });
+    // Edited as text, parsed when saving
+    let time_ranges = create_rw_signal(
+        options
+            .get_untracked()
+            .time_ranges
+            .iter()
+            .map(|r| (format_timestamp(r.start), r.end.map(format_timestamp).unwrap_or_default()))
+            .collect::<Vec<_>>(),
+    );
+    let save = move |_| {
+        let notification_context = use_context::<NotificationContext>().unwrap();
+        let parsed: Option<Vec<TimeRange>> = time_ranges
+            .get_untracked()
+            .iter()
+            .map(|(start, end)| {
+                let end = if end.trim().is_empty() { None } else { Some(parse_timestamp(end)?) };
+                Some(TimeRange { start: parse_timestamp(start)?, end })
+            })
+            .collect();
+        let Some(parsed) = parsed else {
+            notification_context.add_notification(Notification {
+                text: "Times must look like 90, 1:30 or 1:02:03".into(),
+                notification_type: NotificationType::Warning,
+            });
+            return;
+        };
+        options.update(|o| {
+            o.subtitles = custom_subtitles.get_untracked().then(|| subtitles.get_untracked());
+            o.sponsorblock = custom_sponsorblock.get_untracked().then(|| sponsorblock.get_untracked());
+            o.loudness = custom_loudness.get_untracked().then(|| loudness.get_untracked());
+            o.time_ranges = parsed;
+        });
+        let id = download.get_untracked().metadata.id;
+        let options = options.get_untracked();
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&DownloadOptionsArgs {
+                id: id.clone(),
+                options: options.clone(),
+            })
+            .unwrap();
+            match invoke("set_download_options", args).await {
+                Ok(_) => {
+                    set_download.update(|d| d.options = options.clone());
+                    if let Some(downloads) = use_context::<RwSignal<Vec<Download>>>() {
+                        downloads.update(|dls| {
+                            if let Some(download) = dls.iter_mut().find(|d| d.metadata.id == id) {
+                                download.options = options;
+                            }
+                        });
+                    }
+                    show.set(false);
+                }
+                Err(err) => {
+                    let err: YaydlError = serde_wasm_bindgen::from_value(err).unwrap();
+                    notification_context.add_notification(Notification {
+                        text: err.to_string(),
+                        notification_type: NotificationType::Warning,
+                    });
+                }
+            }
+        });
+    };
+    view! {
+        <Show when=move || show.get()>
+            <div class="fixed inset-0 bg-black bg-opacity-50 flex items-center justify-center z-50">
+                <div class="bg-white rounded-lg shadow-lg p-8 flex flex-col min-w-[500px] max-w-[700px] max-h-[90vh] overflow-y-auto">
+                    <h2 class="text-xl font-bold mb-4 line-clamp-1">{move || download.get().metadata.title}</h2>
+                    <Show when=move || !download.get().metadata.chapters.is_empty()>
+                        <label class="flex space-x-2 items-center mb-2">
+                            <input
+                                type="checkbox"
+                                prop:checked=move || options.get().split_chapters
+                                on:change=move |ev| options.update(|o| o.split_chapters = event_target_checked(&ev))
+                            />
+                            <span>{move || format!("Split into {} tracks by chapter", download.get().metadata.chapters.len())}</span>
+                        </label>
+                    </Show>
+                    <h3>"Time ranges"</h3>
+                    <p class="text-sm mb-1">"Only these parts are downloaded, each into its own file. Leave the end empty to download until the end."</p>
+                    {move || time_ranges
+                        .get()
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, (start, end))| view! {
+                            <div class="flex space-x-1 items-center mb-1">
+                                <input
+                                    type="text"
+                                    placeholder="start, e.g. 1:30"
+                                    class="p-1 rounded-md border w-32"
+                                    prop:value=start
+                                    on:change=move |ev| time_ranges.update(|r| r[index].0 = event_target_value(&ev))
+                                />
+                                <span>"-"</span>
+                                <input
+                                    type="text"
+                                    placeholder="end"
+                                    class="p-1 rounded-md border w-32"
+                                    prop:value=end
+                                    on:change=move |ev| time_ranges.update(|r| r[index].1 = event_target_value(&ev))
+                                />
+                                <button on:click=move |_| time_ranges.update(|r| { r.remove(index); })>
+                                    <Icon icon=icondata::AiDeleteOutlined class="h-6 w-6 text-gray-600 hover:text-gray-800" />
+                                </button>
+                            </div>
+                        })
+                        .collect_view()}
+                    <button
+                        class="border-2 border-gray-500 h-8 w-32 mb-2 rounded-md bg-gray-400 hover:bg-gray-500 shadow-md"
+                        on:click=move |_| time_ranges.update(|r| r.push((String::new(), String::new())))
+                    >
+                        "Add range"
+                    </button>
+                    <h3>"Subtitles"</h3>
+                    <label class="flex space-x-2 items-center mb-2">
+                        <input
+                            type="checkbox"
+                            prop:checked=custom_subtitles
+                            on:change=move |ev| set_custom_subtitles.set(event_target_checked(&ev))
+                        />
+                        <span>"Override the default subtitle settings"</span>
+                    </label>
+                    <Show when=move || custom_subtitles.get()>
+                        <SubtitleOptionsEditor options=subtitles available />
+                    </Show>
+                    <h3 class="mt-2">"SponsorBlock"</h3>
+                    <label class="flex space-x-2 items-center mb-2">
+                        <input
+                            type="checkbox"
+                            prop:checked=custom_sponsorblock
+                            on:change=move |ev| set_custom_sponsorblock.set(event_target_checked(&ev))
+                        />
+                        <span>"Override the default SponsorBlock settings"</span>
+                    </label>
+                    <Show when=move || custom_sponsorblock.get()>
+                        <SponsorBlockEditor settings=sponsorblock />
+                    </Show>
+                    <h3 class="mt-2">"Loudness"</h3>
+                    <label class="flex space-x-2 items-center mb-2">
+                        <input
+                            type="checkbox"
+                            prop:checked=custom_loudness
+                            on:change=move |ev| set_custom_loudness.set(event_target_checked(&ev))
+                        />
+                        <span>"Override the default loudness settings"</span>
+                    </label>
+                    <Show when=move || custom_loudness.get()>
+                        <LoudnessEditor settings=loudness />
+                    </Show>
+                    <div class="flex space-x-4 mt-4 justify-end">
+                        <button class="bg-blue-600 text-white px-4 py-2 rounded hover:bg-blue-700 font-semibold" on:click=save>
+                            Save
+                        </button>
+                        <button class="bg-gray-300 text-gray-800 px-4 py-2 rounded hover:bg-gray-400 font-semibold" on:click=move |_| show.set(false)>
+                            Cancel
+                        </button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+    }
+}
+#[component]
+pub fn Settings() -> impl IntoView {
+    let (output_dir, set_output_dir) = create_signal(String::new());
+    let (run_in_background, set_run_in_background) = create_signal(false);
+    let notifications = create_rw_signal(NotificationSettings::default());
+    let (rate_limit, set_rate_limit) = create_signal(String::new());
+    let schedule = create_rw_signal(Vec::<ScheduleWindow>::new());
+    let network = create_rw_signal(NetworkSettings::default());
+    let cookies = create_rw_signal(CookieSource::None);
+    let (browser, set_browser) = create_signal(COOKIE_BROWSERS[4].to_string());
+    let (profile, set_profile) = create_signal(String::new());
+    let binaries = create_rw_signal(BinarySettings::default());
+    let binary_versions = create_rw_signal(Vec::<(Tool, Result<String, String>)>::new());
+    let subtitles = create_rw_signal(SubtitleOptions::default());
+    let sponsorblock = create_rw_signal(SponsorBlockSettings::default());
+    let loudness = create_rw_signal(LoudnessSettings::default());
+    let post_processing = create_rw_signal(Vec::<PostProcessStep>::new());
+    let yt_dlp_version = create_rw_signal(None::<YtDlpVersion>);
+    let (yt_dlp_busy, set_yt_dlp_busy) = create_signal(false);
+    create_effect(move |_| {
+        spawn_local(async move {
+            if let Ok(js_val) = invoke("get_settings", JsValue::NULL).await {
+                let settings: Settings = serde_wasm_bindgen::from_value(js_val).unwrap();
+                set_output_dir.set(settings.output_dir.display().to_string());
+                set_run_in_background.set(settings.run_in_background);
+                notifications.set(settings.notifications);
+                set_rate_limit.set(settings.rate_limit.unwrap_or_default());
+                schedule.set(settings.schedule);
+                network.set(settings.network);
+                if let CookieSource::Browser { browser, profile } = &settings.cookies {
+                    set_browser.set(browser.clone());
+                    set_profile.set(profile.clone().unwrap_or_default());
+                }
+                cookies.set(settings.cookies);
+                binaries.set(settings.binaries);
+                subtitles.set(settings.subtitles);
+                sponsorblock.set(settings.sponsorblock);
+                loudness.set(settings.loudness);
+                post_processing.set(settings.post_processing);
+            }
+        });
+    });
+    let check_binaries = move || {
+        spawn_local(async move {
+            let mut versions = vec![];
+            for tool in [Tool::YtDlp, Tool::Ffmpeg] {
+                let args = serde_wasm_bindgen::to_value(&ToolArgs { tool }).unwrap();
+                let version = match invoke("binary_version", args).await {
+                    Ok(js_val) => Ok(serde_wasm_bindgen::from_value(js_val).unwrap()),
+                    Err(err) => Err(serde_wasm_bindgen::from_value::<YaydlError>(err).unwrap().to_string()),
+                };
+                versions.push((tool, version));
+            }
+            binary_versions.set(versions);
+        });
+    };
+    check_binaries();
+    let save_binaries = move |_| {
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&ValueArgs {
+                value: binaries.get_untracked(),
+            })
+            .unwrap();
+            let notification_context = use_context::<NotificationContext>().unwrap();
+            match invoke("set_binary_settings", args).await {
+                Ok(_) => notification_context.add_notification(Notification {
+                    text: "Binary settings updated successfully".into(),
+                    notification_type: NotificationType::Success,
+                }),
+                Err(err) => {
+                    let err: YaydlError = serde_wasm_bindgen::from_value(err).unwrap();
+                    notification_context.add_notification(Notification {
+                        text: err.to_string(),
+                        notification_type: NotificationType::Warning,
+                    });
+                }
+            }
+            check_binaries();
+        });
+    };
+    let toggle_run_in_background = move |ev| {
+        let value = event_target_checked(&ev);
+        set_run_in_background.set(value);
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&ValueArgs { value }).unwrap();
+            invoke_with_args("set_run_in_background", args).await;
+        });
+    };
+    let toggle_notification = move |ev, toggle: fn(&mut NotificationSettings, bool)| {
+        notifications.update(|n| toggle(n, event_target_checked(&ev)));
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&ValueArgs {
+                value: notifications.get_untracked(),
+            })
+            .unwrap();
+            invoke_with_args("set_notification_settings", args).await;
+        });
+    };
+    let save_rate_limit = move |ev| {
+        let value = event_target_value(&ev);
+        set_rate_limit.set(value.clone());
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&ValueArgs {
+                value: Some(value).filter(|v| !v.is_empty()),
+            })
+            .unwrap();
+            if let Err(err) = invoke("set_rate_limit", args).await {
+                let err: YaydlError = serde_wasm_bindgen::from_value(err).unwrap();
+                let notification_context = use_context::<NotificationContext>().unwrap();
+                notification_context.add_notification(Notification {
+                    text: err.to_string(),
+                    notification_type: NotificationType::Warning,
+                });
+            }
+        });
+    };
+    let save_schedule = move || {
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&ValueArgs {
+                value: schedule.get_untracked(),
+            })
+            .unwrap();
+            if let Err(err) = invoke("set_schedule", args).await {
+                let err: YaydlError = serde_wasm_bindgen::from_value(err).unwrap();
+                let notification_context = use_context::<NotificationContext>().unwrap();
+                notification_context.add_notification(Notification {
+                    text: err.to_string(),
+                    notification_type: NotificationType::Warning,
+                });
+            }
+        });
+    };
+    let save_network = move |_| {
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&ValueArgs {
+                value: network.get_untracked(),
+            })
+            .unwrap();
+            let notification_context = use_context::<NotificationContext>().unwrap();
+            match invoke("set_network_settings", args).await {
+                Ok(_) => notification_context.add_notification(Notification {
+                    text: "Network settings updated successfully".into(),
+                    notification_type: NotificationType::Success,
+                }),
+                Err(err) => {
+                    let err: YaydlError = serde_wasm_bindgen::from_value(err).unwrap();
+                    notification_context.add_notification(Notification {
+                        text: err.to_string(),
+                        notification_type: NotificationType::Warning,
+                    });
+                }
+            }
+        });
+    };
+    let test_connection = move |_| {
+        spawn_local(async move {
+            let notification_context = use_context::<NotificationContext>().unwrap();
+            match invoke("test_connection", JsValue::NULL).await {
+                Ok(_) => notification_context.add_notification(Notification {
+                    text: "Connection test succeeded".into(),
+                    notification_type: NotificationType::Success,
+                }),
+                Err(err) => {
+                    let err: YaydlError = serde_wasm_bindgen::from_value(err).unwrap();
+                    notification_context.add_notification(Notification {
+                        text: err.to_string(),
+                        notification_type: NotificationType::Error,
+                    });
+                }
+            }
+        });
+    };
+    let save_subtitles = move |_| {
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&ValueArgs {
+                value: subtitles.get_untracked(),
+            })
+            .unwrap();
+            let notification_context = use_context::<NotificationContext>().unwrap();
+            match invoke("set_subtitle_options", args).await {
+                Ok(_) => notification_context.add_notification(Notification {
+                    text: "Subtitle settings updated successfully".into(),
+                    notification_type: NotificationType::Success,
+                }),
+                Err(err) => {
+                    let err: YaydlError = serde_wasm_bindgen::from_value(err).unwrap();
+                    notification_context.add_notification(Notification {
+                        text: err.to_string(),
+                        notification_type: NotificationType::Warning,
+                    });
+                }
+            }
+        });
+    };
+    let save_sponsorblock = move |_| {
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&ValueArgs {
+                value: sponsorblock.get_untracked(),
+            })
+            .unwrap();
+            let notification_context = use_context::<NotificationContext>().unwrap();
+            match invoke("set_sponsorblock_settings", args).await {
+                Ok(_) => notification_context.add_notification(Notification {
+                    text: "SponsorBlock settings updated successfully".into(),
+                    notification_type: NotificationType::Success,
+                }),
+                Err(err) => {
+                    let err: YaydlError = serde_wasm_bindgen::from_value(err).unwrap();
+                    notification_context.add_notification(Notification {
+                        text: err.to_string(),
+                        notification_type: NotificationType::Warning,
+                    });
+                }
+            }
+        });
+    };
+    let save_loudness = move |_| {
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&ValueArgs {
+                value: loudness.get_untracked(),
+            })
+            .unwrap();
+            let notification_context = use_context::<NotificationContext>().unwrap();
+            match invoke("set_loudness_settings", args).await {
+                Ok(_) => notification_context.add_notification(Notification {
+                    text: "Loudness settings updated successfully".into(),
+                    notification_type: NotificationType::Success,
+                }),
+                Err(err) => {
+                    let err: YaydlError = serde_wasm_bindgen::from_value(err).unwrap();
+                    notification_context.add_notification(Notification {
+                        text: err.to_string(),
+                        notification_type: NotificationType::Warning,
+                    });
+                }
+            }
+        });
+    };
+    let save_post_processing = move |_| {
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&ValueArgs {
+                value: post_processing.get_untracked(),
+            })
+            .unwrap();
+            let notification_context = use_context::<NotificationContext>().unwrap();
+            match invoke("set_post_processing", args).await {
+                Ok(_) => notification_context.add_notification(Notification {
+                    text: "Post-processing updated successfully".into(),
+                    notification_type: NotificationType::Success,
+                }),
+                Err(err) => {
+                    let err: YaydlError = serde_wasm_bindgen::from_value(err).unwrap();
+                    notification_context.add_notification(Notification {
+                        text: err.to_string(),
+                        notification_type: NotificationType::Warning,
+                    });
+                }
+            }
+        });
+    };
+    let import_cookies = move |_| {
+        spawn_local(async move {
+            let notification_context = use_context::<NotificationContext>().unwrap();
+            match invoke("import_cookies", JsValue::NULL).await {
+                Ok(_) => {
+                    cookies.set(CookieSource::File);
+                    notification_context.add_notification(Notification {
+                        text: "Cookies imported successfully".into(),
+                        notification_type: NotificationType::Success,
+                    });
+                }
+                Err(err) => {
+                    let err: YaydlError = serde_wasm_bindgen::from_value(err).unwrap();
+                    if !matches!(err, YaydlError::FileSelectionFailed) {
+                        notification_context.add_notification(Notification {
+                            text: err.to_string(),
+                            notification_type: NotificationType::Error,
+                        });
+                    }
+                }
+            }
+        });
+    };
+    let set_cookie_source = move |source: CookieSource| {
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&ValueArgs {
+                value: source.clone(),
+            })
+            .unwrap();
+            match invoke("set_cookie_source", args).await {
+                Ok(_) => cookies.set(source),
+                Err(err) => {
+                    let err: YaydlError = serde_wasm_bindgen::from_value(err).unwrap();
+                    let notification_context = use_context::<NotificationContext>().unwrap();
+                    notification_context.add_notification(Notification {
+                        text: err.to_string(),
+                        notification_type: NotificationType::Warning,
+                    });
+                }
+            }
+        });
+    };
+    let check_yt_dlp = move || {
+        spawn_local(async move {
+            set_yt_dlp_busy.set(true);
+            if let Ok(js_val) = invoke("yt_dlp_version", JsValue::NULL).await {
+                yt_dlp_version.set(serde_wasm_bindgen::from_value(js_val).ok());
+            }
+            set_yt_dlp_busy.set(false);
+        });
+    };
+    check_yt_dlp();
+    let run_yt_dlp_command = move |cmd: &'static str| {
+        spawn_local(async move {
+            set_yt_dlp_busy.set(true);
+            let notification_context = use_context::<NotificationContext>().unwrap();
+            match invoke(cmd, JsValue::NULL).await {
+                Ok(js_val) => {
+                    let version: String = serde_wasm_bindgen::from_value(js_val).unwrap();
+                    notification_context.add_notification(Notification {
+                        text: format!("Now using yt-dlp {version}"),
+                        notification_type: NotificationType::Success,
+                    });
+                }
+                Err(err) => {
+                    let err: YaydlError = serde_wasm_bindgen::from_value(err).unwrap();
+                    let notification_type = match err {
+                        YaydlError::YtDlpUpdateError(YtDlpUpdateError::AlreadyUpToDate(_)) => NotificationType::Info,
+                        _ => NotificationType::Error,
+                    };
+                    notification_context.add_notification(Notification {
+                        text: err.to_string(),
+                        notification_type,
+                    });
+                }
+            }
+            set_yt_dlp_busy.set(false);
+            check_yt_dlp();
+        });
+    };
+    let add_window = move |_| {
+        schedule.update(|s| {
+            s.push(ScheduleWindow {
+                start: "22:00".into(),
+                end: "06:00".into(),
+            })
+        });
+        save_schedule();
+    };
+    let get_output_dir = move |_| {
+        spawn_local(async move {
+            if let Ok(js_val) = invoke("choose_output_dir", JsValue::NULL).await {
+                let notification_context = use_context::<NotificationContext>().unwrap();
+                let path = serde_wasm_bindgen::from_value(js_val).unwrap();
+                set_output_dir.set(path);
+                notification_context.add_notification(Notification {
+                    text: "Output directory updated successfully".into(),
+                    notification_type: NotificationType::Success,
+                });
+            }
+        });
+    };
+    view! {
+        <div class="flex items-center justify-center h-12 p-2 bg-gray-300">
+        </div>
+        <div class="flex flex-col h-full p-2 overflow-auto">
+            <h2 class="text-xl">Settings</h2>
+            <span class="h-2 w-full border-2"></span>
+            <br />
+            <div class="flex space-x-1 items-center">
+                <button on:click=get_output_dir class="border-2 border-gray-500 h-8 w-52 rounded-md bg-gray-400 hover:bg-gray-500 shadow-md">
+                    "Set output directory"
+                </button>
+                <p class="bg-blue-300 p-1 rounded-md w-full">{output_dir}</p>
+            </div>
+            <label class="flex space-x-2 items-center mt-2">
+                <input type="checkbox" prop:checked=run_in_background on:change=toggle_run_in_background />
+                <span>"Keep downloading in the background when the window is closed"</span>
+            </label>
+            <h3 class="mt-4">Desktop notifications</h3>
+            <label class="flex space-x-2 items-center">
+                <input
+                    type="checkbox"
+                    prop:checked=move || notifications.get().download_finished
+                    on:change=move |ev| toggle_notification(ev, |n, v| n.download_finished = v)
+                />
+                <span>"Download finished"</span>
+            </label>
+            <label class="flex space-x-2 items-center">
+                <input
+                    type="checkbox"
+                    prop:checked=move || notifications.get().download_failed
+                    on:change=move |ev| toggle_notification(ev, |n, v| n.download_failed = v)
+                />
+                <span>"Download failed"</span>
+            </label>
+            <label class="flex space-x-2 items-center">
+                <input
+                    type="checkbox"
+                    prop:checked=move || notifications.get().queue_finished
+                    on:change=move |ev| toggle_notification(ev, |n, v| n.queue_finished = v)
+                />
+                <span>"Queue finished"</span>
+            </label>
+            <h3 class="mt-4">Subtitles</h3>
+            <SubtitleOptionsEditor options=subtitles />
+            <button on:click=save_subtitles class="border-2 border-gray-500 h-8 w-52 mt-2 rounded-md bg-gray-400 hover:bg-gray-500 shadow-md">
+                "Save subtitle settings"
+            </button>
+            <h3 class="mt-4">SponsorBlock</h3>
+            <SponsorBlockEditor settings=sponsorblock />
+            <button on:click=save_sponsorblock class="border-2 border-gray-500 h-8 w-52 mt-2 rounded-md bg-gray-400 hover:bg-gray-500 shadow-md">
+                "Save SponsorBlock settings"
+            </button>
+            <h3 class="mt-4">Loudness</h3>
+            <LoudnessEditor settings=loudness />
+            <button on:click=save_loudness class="border-2 border-gray-500 h-8 w-52 mt-2 rounded-md bg-gray-400 hover:bg-gray-500 shadow-md">
+                "Save loudness settings"
+            </button>
+            <h3 class="mt-4">Post-processing</h3>
+            <PostProcessingEditor steps=post_processing />
+            <button on:click=save_post_processing class="border-2 border-gray-500 h-8 w-52 mt-2 rounded-md bg-gray-400 hover:bg-gray-500 shadow-md">
+                "Save post-processing"
+            </button>
+            <h3 class="mt-4">Bandwidth</h3>
+            <div class="flex space-x-2 items-center">
+                <span>"Rate limit"</span>
+                <input
+                    type="text"
+                    placeholder="e.g. 500K or 2M, empty for unlimited"
+                    class="p-1 rounded-md w-64"
+                    prop:value=rate_limit
+                    on:change=save_rate_limit
+                />
+            </div>
+            <p class="text-sm">"Applies to downloads started after the change, running downloads keep their limit"</p>
+            <div class="flex space-x-2 items-center mt-2">
+                <span>"Only download between"</span>
+                <button on:click=add_window class="h-6 w-6">
+                    <Icon icon=icondata::AiPlusSquareOutlined class="h-full w-full fill-gray-500 hover:fill-gray-600"/>
+                </button>
+            </div>
+            {move || schedule.get().into_iter().enumerate()
+                .map(|(i, window)| view! {
+                    <div class="flex space-x-2 items-center mt-1">
+                        <input
+                            type="time"
+                            class="p-1 rounded-md"
+                            prop:value=window.start
+                            on:change=move |ev| {
+                                schedule.update(|s| s[i].start = event_target_value(&ev));
+                                save_schedule();
+                            }
+                        />
+                        <span>"-"</span>
+                        <input
+                            type="time"
+                            class="p-1 rounded-md"
+                            prop:value=window.end
+                            on:change=move |ev| {
+                                schedule.update(|s| s[i].end = event_target_value(&ev));
+                                save_schedule();
+                            }
+                        />
+                        <button
+                            class="h-6 w-6"
+                            on:click=move |_| {
+                                schedule.update(|s| {
+                                    s.remove(i);
+                                });
+                                save_schedule();
+                            }
+                        >
+                            <Icon icon=icondata::CgClose class="h-full w-full text-gray-500 hover:text-gray-600"/>
+                        </button>
+                    </div>
+                })
+                .collect_view()
+            }
+            <h3 class="mt-4">Binaries</h3>
+            <div class="grid grid-cols-[10rem_10rem_1fr_10rem] gap-1 items-center">
+                {[Tool::YtDlp, Tool::Ffmpeg]
+                    .into_iter()
+                    .map(|tool| {
+                        let source = move || match tool {
+                            Tool::YtDlp => binaries.get().yt_dlp,
+                            Tool::Ffmpeg | Tool::Ffprobe | Tool::Ffplay => binaries.get().ffmpeg,
+                        };
+                        let set_source = move |value: BinarySource| {
+                            binaries.update(|b| match tool {
+                                Tool::YtDlp => b.yt_dlp = value,
+                                Tool::Ffmpeg | Tool::Ffprobe | Tool::Ffplay => b.ffmpeg = value,
+                            })
+                        };
+                        view! {
+                            <span>{tool.name()}</span>
+                            <select
+                                class="p-1 rounded-md"
+                                on:change=move |ev| {
+                                    set_source(match event_target_value(&ev).as_str() {
+                                        "system" => BinarySource::System,
+                                        "custom" => BinarySource::Custom(Default::default()),
+                                        _ => BinarySource::Bundled,
+                                    })
+                                }
+                            >
+                                <option value="bundled" selected=move || source() == BinarySource::Bundled>"Bundled"</option>
+                                <option value="system" selected=move || source() == BinarySource::System>"From PATH"</option>
+                                <option value="custom" selected=move || matches!(source(), BinarySource::Custom(_))>"Custom path"</option>
+                            </select>
+                            <Show
+                                when=move || matches!(source(), BinarySource::Custom(_))
+                                fallback=|| view! { <span></span> }
+                            >
+                                <input
+                                    type="text"
+                                    class="p-1 rounded-md"
+                                    placeholder=format!("Path to {}", tool.name())
+                                    prop:value=move || match source() {
+                                        BinarySource::Custom(path) => path.display().to_string(),
+                                        _ => String::new(),
+                                    }
+                                    on:change=move |ev| set_source(BinarySource::Custom(event_target_value(&ev).into()))
+                                />
+                            </Show>
+                            <span class="text-sm">
+                                {move || binary_versions
+                                    .get()
+                                    .into_iter()
+                                    .find(|(t, _)| *t == tool)
+                                    .map(|(_, version)| version.unwrap_or_else(|err| err))
+                                    .unwrap_or_default()}
+                            </span>
+                        }
+                    })
+                    .collect_view()}
+            </div>
+            <button on:click=save_binaries class="border-2 border-gray-500 h-8 w-52 mt-2 rounded-md bg-gray-400 hover:bg-gray-500 shadow-md">
+                "Save binary settings"
+            </button>
+            <h3 class="mt-4">yt-dlp</h3>
+            <p class="text-sm">
+                {move || match yt_dlp_version.get() {
+                    Some(version) => {
+                        let source = if version.managed { "updated" } else { "bundled" };
+                        let latest = match version.latest {
+                            Some(latest) if latest == version.installed => ", up to date".to_string(),
+                            Some(latest) => format!(", {latest} available"),
+                            None => String::new(),
+                        };
+                        format!("Version {} ({source}){latest}", version.installed)
+                    }
+                    None => "Checking version...".to_string(),
+                }}
+            </p>
+            <div class="flex space-x-1 items-center mt-1">
+                <button
+                    on:click=move |_| run_yt_dlp_command("update_yt_dlp")
+                    disabled=yt_dlp_busy
+                    class="border-2 border-gray-500 h-8 w-52 rounded-md bg-gray-400 hover:bg-gray-500 shadow-md"
+                >
+                    "Update yt-dlp"
+                </button>
+                <Show when=move || yt_dlp_version.get().is_some_and(|v| v.managed)>
+                    <button
+                        on:click=move |_| run_yt_dlp_command("rollback_yt_dlp")
+                        disabled=yt_dlp_busy
+                        class="border-2 border-gray-500 h-8 w-52 rounded-md bg-gray-400 hover:bg-gray-500 shadow-md"
+                    >
+                        "Roll back"
+                    </button>
+                </Show>
+            </div>
+            <h3 class="mt-4">Cookies</h3>
+            <p class="text-sm">
+                {move || match cookies.get() {
+                    CookieSource::None => "Not using cookies".to_string(),
+                    CookieSource::File => "Using imported cookies.txt".to_string(),
+                    CookieSource::Browser { browser, profile: Some(profile) } => format!("Using cookies from {browser} ({profile})"),
+                    CookieSource::Browser { browser, profile: None } => format!("Using cookies from {browser}"),
+                }}
+            </p>
+            <div class="flex space-x-1 items-center mt-1">
+                <button on:click=import_cookies class="border-2 border-gray-500 h-8 w-52 rounded-md bg-gray-400 hover:bg-gray-500 shadow-md">
+                    "Import cookies.txt"
+                </button>
+                <button on:click=move |_| set_cookie_source(CookieSource::None) class="border-2 border-gray-500 h-8 w-52 rounded-md bg-gray-400 hover:bg-gray-500 shadow-md">
+                    "Don't use cookies"
+                </button>
+            </div>
+            <div class="flex space-x-1 items-center mt-1">
+                <select class="p-1 rounded-md" on:change=move |ev| set_browser.set(event_target_value(&ev))>
+                    {COOKIE_BROWSERS.into_iter()
+                        .map(|b| view! { <option value=b selected=move || browser.get() == b>{b}</option> })
+                        .collect_view()}
+                </select>
+                <input
+                    type="text"
+                    placeholder="Profile (optional)"
+                    class="p-1 rounded-md"
+                    prop:value=profile
+                    on:change=move |ev| set_profile.set(event_target_value(&ev))
+                />
+                <button
+                    on:click=move |_| set_cookie_source(CookieSource::Browser {
+                        browser: browser.get_untracked(),
+                        profile: Some(profile.get_untracked()).filter(|p| !p.is_empty()),
+                    })
+                    class="border-2 border-gray-500 h-8 w-52 rounded-md bg-gray-400 hover:bg-gray-500 shadow-md"
+                >
+                    "Use browser cookies"
+                </button>
+            </div>
+            <h3 class="mt-4">Network</h3>
+            <div class="grid grid-cols-[10rem_1fr] gap-1 items-center">
+                <span>"Proxy"</span>
+                <input
+                    type="text"
+                    placeholder="e.g. socks5://127.0.0.1:1080"
+                    class="p-1 rounded-md"
+                    prop:value=move || network.get().proxy.unwrap_or_default()
+                    on:change=move |ev| {
+                        let value = event_target_value(&ev);
+                        network.update(|n| n.proxy = Some(value).filter(|v| !v.is_empty()));
+                    }
+                />
+                <span>"IP version"</span>
+                <select
+                    class="p-1 rounded-md"
+                    on:change=move |ev| {
+                        let ip_version = match event_target_value(&ev).as_str() {
+                            "v4" => IpVersion::V4,
+                            "v6" => IpVersion::V6,
+                            _ => IpVersion::Any,
+                        };
+                        network.update(|n| n.ip_version = ip_version);
+                    }
+                >
+                    <option value="any" selected=move || network.get().ip_version == IpVersion::Any>"Any"</option>
+                    <option value="v4" selected=move || network.get().ip_version == IpVersion::V4>"IPv4 only"</option>
+                    <option value="v6" selected=move || network.get().ip_version == IpVersion::V6>"IPv6 only"</option>
+                </select>
+                <span>"Source address"</span>
+                <input
+                    type="text"
+                    class="p-1 rounded-md"
+                    prop:value=move || network.get().source_address.unwrap_or_default()
+                    on:change=move |ev| {
+                        let value = event_target_value(&ev);
+                        network.update(|n| n.source_address = Some(value).filter(|v| !v.is_empty()));
+                    }
+                />
+                <span>"Socket timeout (s)"</span>
+                <input
+                    type="number"
+                    min="1"
+                    class="p-1 rounded-md"
+                    prop:value=move || network.get().socket_timeout.map(|t| t.to_string()).unwrap_or_default()
+                    on:change=move |ev| {
+                        let value = event_target_value(&ev).parse().ok();
+                        network.update(|n| n.socket_timeout = value);
+                    }
+                />
+            </div>
+            <div class="flex space-x-1 mt-2">
+                <button on:click=save_network class="border-2 border-gray-500 h-8 w-52 rounded-md bg-gray-400 hover:bg-gray-500 shadow-md">
+                    "Save network settings"
+                </button>
+                <button on:click=test_connection class="border-2 border-gray-500 h-8 w-52 rounded-md bg-gray-400 hover:bg-gray-500 shadow-md">
+                    "Test connection"
+                </button>
+            </div>
+        </div>
+    }
+}
+#[component]
+pub fn About() -> impl IntoView {
+    let diagnostics = create_rw_signal(None::<Diagnostics>);
+    let run_diagnostics = move || {
+        diagnostics.set(None);
+        spawn_local(async move {
+            let js_val = invoke_without_args("diagnostics").await;
+            diagnostics.set(serde_wasm_bindgen::from_value(js_val).ok());
+        });
+    };
+    run_diagnostics();
+    view! {
+        <div class="flex flex-col w-full h-full p-2 overflow-y-auto">
+            <h2 class="text-xl font-bold">"About yaydl"</h2>
+            <Show
+                when=move || diagnostics.get().is_some()
+                fallback=|| view! { <p class="mt-2">"Running diagnostics..."</p> }
+            >
+                {move || diagnostics.get().map(|d| view! {
+                    <div class="grid grid-cols-[10rem_1fr] gap-1 mt-2 text-sm break-all">
+                        <span>"Version"</span>
+                        <span>{d.app_version}</span>
+                        <span>"System"</span>
+                        <span>{d.os}</span>
+                    </div>
+                    <h3 class="mt-4">"Binaries"</h3>
+                    <div class="grid grid-cols-[10rem_10rem_1fr] gap-1 text-sm break-all">
+                        {d.binaries
+                            .into_iter()
+                            .map(|check| {
+                                let ok = check.version.is_ok();
+                                view! {
+                                    <span>{check.tool.name()}</span>
+                                    <span class:text-red-600=!ok>
+                                        {check.version.unwrap_or_else(|err| err)}
+                                    </span>
+                                    <span>{check.path.map(|p| p.display().to_string()).unwrap_or_default()}</span>
+                                }
+                            })
+                            .collect_view()}
+                    </div>
+                    <h3 class="mt-4">"Paths"</h3>
+                    <div class="grid grid-cols-[10rem_1fr] gap-1 text-sm break-all">
+                        <span>"Output directory"</span>
+                        <span class:text-red-600=!d.output_dir_writable>
+                            {d.output_dir.display().to_string()}
+                            {if d.output_dir_writable { "" } else { " (not writable)" }}
+                        </span>
+                        <span>"Settings file"</span>
+                        <span>{d.settings_file.display().to_string()}</span>
+                        <span>"Config directory"</span>
+                        <span>{d.config_dir.display().to_string()}</span>
+                        <span>"Data directory"</span>
+                        <span>{d.data_dir.display().to_string()}</span>
+                    </div>
+                })}
+            </Show>
+            <button
+                on:click=move |_| run_diagnostics()
+                class="border-2 border-gray-500 h-8 w-52 mt-4 rounded-md bg-gray-400 hover:bg-gray-500 shadow-md"
+            >
+                "Run diagnostics again"
+            </button>
+        </div>
+    }
+}
+/// One file of the library with its actions and tag editor.
+#[component]
+pub fn LibraryRow(item: LibraryItem, items: RwSignal<Vec<LibraryItem>>) -> impl IntoView {
+    let playback = use_context::<RwSignal<Option<Playback>>>().unwrap();
+    let id = item.path.display().to_string();
+    let own_playback = move || playback.get().filter(|p| p.id == id);
+    let tags = create_rw_signal(item.tags.clone());
+    let (editing, set_editing) = create_signal(false);
+    let (confirm_delete, set_confirm_delete) = create_signal(false);
+    let path = item.path.clone();
+    // Commands returning the updated library
+    let run = move |cmd: &'static str, args: JsValue| {
+        spawn_local(async move {
+            let notification_context = use_context::<NotificationContext>().unwrap();
+            match invoke(cmd, args).await {
+                Ok(js_val) => {
+                    if let Ok(updated) = serde_wasm_bindgen::from_value::<Vec<LibraryItem>>(js_val) {
+                        items.set(updated);
+                    }
+                }
+                Err(err) => {
+                    let err: YaydlError = serde_wasm_bindgen::from_value(err).unwrap();
+                    notification_context.add_notification(Notification {
+                        text: err.to_string(),
+                        notification_type: NotificationType::Error,
+                    });
+                }
+            }
+        });
+    };
+    let path_args = move || serde_wasm_bindgen::to_value(&PathArgs { path: path.clone() }).unwrap();
+    let play = {
+        let path_args = path_args.clone();
+        move |_| run("play_file", path_args())
+    };
+    let reveal = {
+        let path_args = path_args.clone();
+        move |_| run("reveal_file", path_args())
+    };
+    let delete = {
+        let path_args = path_args.clone();
+        move |_| {
+            if confirm_delete.get_untracked() {
+                run("delete_file", path_args());
+            } else {
+                set_confirm_delete.set(true);
+            }
+        }
+    };
+    let save_tags = {
+        let path = item.path.clone();
+        move |_| {
+            let args = serde_wasm_bindgen::to_value(&RetagArgs {
+                path: path.clone(),
+                tags: tags.get_untracked(),
+            })
+            .unwrap();
+            run("retag_file", args);
+            set_editing.set(false);
+        }
+    };
+    let tag_input = move |label: &'static str, get: fn(&LibraryTags) -> String, set: fn(&mut LibraryTags, String)| {
+        view! {
+            <span>{label}</span>
+            <input
+                type="text"
+                class="p-1 rounded-md"
+                prop:value=move || get(&tags.get())
+                on:change=move |ev| tags.update(|t| set(t, event_target_value(&ev)))
+            />
+        }
+    };
+    let details = [
+        Some(item.tags.album.clone()).filter(|a| !a.is_empty()),
+        item.duration.map(format_timestamp),
+        Some(format!("{:.1} MB", item.size as f64 / 1_000_000.0)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(" · ");
+    view! {
+        <li class="border-b border-gray-400 py-1">
+            <div class="flex items-center space-x-2">
+                <div class="w-full">
+                    <p class="line-clamp-1" title=item.path.display().to_string()>{item.tags.title.clone()}</p>
+                    <p class="text-sm flex space-x-2">
+                        <span>{details}</span>
+                        {item.source.clone().map(|url| view! { <a class="underline" href=url target="_blank">"Source"</a> })}
+                    </p>
+                </div>
+                {
+                    let own_playback = own_playback.clone();
+                    move || own_playback().map(|playback| view! { <PlaybackControls playback /> })
+                }
+                <Show when=move || own_playback().is_none()>
+                    <button title="Play" on:click=play.clone()>
+                        <Icon icon=icondata::AiPlayCircleOutlined class="h-6 w-6 text-gray-600 hover:text-gray-800" />
+                    </button>
+                </Show>
+                <button title="Show in folder" on:click=reveal>
+                    <Icon icon=icondata::AiFolderOpenOutlined class="h-6 w-6 text-gray-600 hover:text-gray-800" />
+                </button>
+                <button title="Edit tags" on:click=move |_| set_editing.update(|e| *e = !*e)>
+                    <Icon icon=icondata::AiEditOutlined class="h-6 w-6 text-gray-600 hover:text-gray-800" />
+                </button>
+                <button
+                    title=move || if confirm_delete.get() { "Click again to delete" } else { "Delete" }
+                    on:click=delete
+                    on:mouseleave=move |_| set_confirm_delete.set(false)
+                >
+                    <span class=move || if confirm_delete.get() { "text-red-600" } else { "text-gray-600 hover:text-gray-800" }>
+                        <Icon icon=icondata::AiDeleteOutlined class="h-6 w-6" />
+                    </span>
+                </button>
+            </div>
+            <Show when=move || editing.get()>
+                <div class="grid grid-cols-[8rem_1fr] gap-1 items-center mt-1">
+                    {tag_input("Title", |t| t.title.clone(), |t, v| t.title = v)}
+                    {tag_input("Artist", |t| t.artist.clone(), |t, v| t.artist = v)}
+                    {tag_input("Album artist", |t| t.album_artist.clone(), |t, v| t.album_artist = v)}
+                    {tag_input("Album", |t| t.album.clone(), |t, v| t.album = v)}
+                </div>
+                <button on:click=save_tags.clone() class="border-2 border-gray-500 h-8 w-32 mt-1 rounded-md bg-gray-400 hover:bg-gray-500 shadow-md">
+                    "Save tags"
+                </button>
+            </Show>
+        </li>
+    }
+}
+/// Files of the output directory grouped by artist.
+#[component]
+pub fn Library() -> impl IntoView {
+    let items = create_rw_signal(Vec::<LibraryItem>::new());
+    let (busy, set_busy) = create_signal(false);
+    create_effect(move |_| {
+        let closure = Closure::<dyn FnMut(_)>::new(move |s: JsValue| {
+            if let Ok(event) = serde_wasm_bindgen::from_value::<LibraryEvent>(s) {
+                items.set(event.payload);
+            }
+        });
+        spawn_local(async move {
+            listen("library", closure.as_ref().unchecked_ref()).await;
+            closure.forget();
+            let js_val = invoke_without_args("get_library").await;
+            items.set(serde_wasm_bindgen::from_value(js_val).unwrap_or_default());
+        });
+    });
+    let rescan = move |_| {
+        spawn_local(async move {
+            set_busy.set(true);
+            let js_val = invoke_without_args("rescan_library").await;
+            items.set(serde_wasm_bindgen::from_value(js_val).unwrap_or_default());
+            set_busy.set(false);
+        });
+    };
+    // Items arrive sorted by group
+    let groups = move || {
+        let mut groups: Vec<(String, Vec<LibraryItem>)> = Vec::new();
+        for item in items.get() {
+            match groups.last_mut() {
+                Some((group, group_items)) if group == item.group() => group_items.push(item),
+                _ => groups.push((item.group().to_string(), vec![item])),
+            }
+        }
+        groups
+    };
+    view! {
+        <div class="flex items-center h-12 p-2 bg-gray-300 space-x-2">
+            <button
+                on:click=rescan
+                disabled=busy
+                class="border-2 border-gray-500 h-8 w-52 rounded-md bg-gray-400 hover:bg-gray-500 shadow-md"
+            >
+                "Rescan"
+            </button>
+            <span>{move || format!("{} files", items.get().len())}</span>
+        </div>
+        <div class="flex flex-col w-full h-full p-2 overflow-y-auto">
+            <Show when=move || !items.get().is_empty() fallback=|| view! { <p>"No audio files in the output folder yet"</p> }>
+                {move || groups()
+                    .into_iter()
+                    .map(|(group, group_items)| view! {
+                        <h3 class="mt-2">{group}</h3>
+                        <ul>
+                            {group_items.into_iter().map(|item| view! { <LibraryRow item items /> }).collect_view()}
+                        </ul>
+                    })
+                    .collect_view()}
+            </Show>
+        </div>
+    }
+}
+#[component]
+pub fn Subscriptions() -> impl IntoView {
+    let subscriptions = create_rw_signal(Vec::<Subscription>::new());
+    let new_subscription = create_rw_signal(Subscription {
+        enabled: true,
+        ..Default::default()
+    });
+    let (busy, set_busy) = create_signal(false);
+    create_effect(move |_| {
+        spawn_local(async move {
+            let js_val = invoke_without_args("get_subscriptions").await;
+            subscriptions.set(serde_wasm_bindgen::from_value(js_val).unwrap_or_default());
+        });
+    });
+    let run = move |cmd: &'static str, args: JsValue, reset: bool| {
+        spawn_local(async move {
+            set_busy.set(true);
+            match invoke(cmd, args).await {
+                Ok(js_val) => {
+                    subscriptions.set(serde_wasm_bindgen::from_value(js_val).unwrap());
+                    if reset {
+                        new_subscription.set(Subscription {
+                            enabled: true,
+                            ..Default::default()
+                        });
+                    }
+                }
+                Err(err) => {
+                    let err: YaydlError = serde_wasm_bindgen::from_value(err).unwrap();
+                    let notification_context = use_context::<NotificationContext>().unwrap();
+                    notification_context.add_notification(Notification {
+                        text: err.to_string(),
+                        notification_type: NotificationType::Error,
+                    });
+                }
+            }
+            set_busy.set(false);
+        });
+    };
+    let subscribe = move |_| {
+        let args = serde_wasm_bindgen::to_value(&ValueArgs {
+            value: new_subscription.get_untracked(),
+        })
+        .unwrap();
+        run("add_subscription", args, true);
+    };
+    let check_now = move |_| {
+        spawn_local(async move {
+            set_busy.set(true);
+            let js_val = invoke_without_args("check_subscriptions").await;
+            let added: usize = serde_wasm_bindgen::from_value(js_val).unwrap_or(0);
+            let notification_context = use_context::<NotificationContext>().unwrap();
+            notification_context.add_notification(Notification {
+                text: format!("{added} new upload(s) added to the queue"),
+                notification_type: NotificationType::Success,
+            });
+            set_busy.set(false);
+        });
+    };
+    // Durations are stored in seconds but entered in minutes
+    let minutes = |seconds: Option<u32>| seconds.map(|s| (s / 60).to_string()).unwrap_or_default();
+    let seconds = |value: String| value.parse::<u32>().ok().map(|m| m * 60);
+    view! {
+        <div class="flex items-center h-12 p-2 bg-gray-300 space-x-1">
+            <button
+                on:click=check_now
+                disabled=busy
+                class="border-2 border-gray-500 h-8 w-52 rounded-md bg-gray-400 hover:bg-gray-500 shadow-md"
+            >
+                "Check for new uploads"
+            </button>
+        </div>
+        <div class="flex flex-col w-full h-full p-2 overflow-y-auto">
+            <h3>"New subscription"</h3>
+            <div class="grid grid-cols-[10rem_1fr] gap-1 items-center">
+                <span>"Channel or playlist"</span>
+                <input
+                    type="text"
+                    placeholder="https://www.youtube.com/@channel"
+                    class="p-1 rounded-md"
+                    prop:value=move || new_subscription.get().url
+                    on:change=move |ev| new_subscription.update(|s| s.url = event_target_value(&ev))
+                />
+                <span>"Name"</span>
+                <input
+                    type="text"
+                    placeholder="Taken from the channel if empty"
+                    class="p-1 rounded-md"
+                    prop:value=move || new_subscription.get().name
+                    on:change=move |ev| new_subscription.update(|s| s.name = event_target_value(&ev))
+                />
+                <span>"Format"</span>
+                <select
+                    class="p-1 rounded-md"
+                    on:change=move |ev| {
+                        let value = event_target_value(&ev);
+                        new_subscription.update(|s| s.options.output_format = Some(value).filter(|v| !v.is_empty()));
+                    }
+                >
+                    <option value="" selected=move || new_subscription.get().options.output_format.is_none()>"Default"</option>
+                    {AUDIO_FORMATS
+                        .map(|f| view! {
+                            <option value=f selected=move || new_subscription.get().options.output_format.as_deref() == Some(f)>{f}</option>
+                        })
+                        .collect_view()}
+                    <optgroup label="Keep video">
+                        {VIDEO_FORMATS
+                            .map(|f| view! {
+                                <option value=f selected=move || new_subscription.get().options.output_format.as_deref() == Some(f)>{f}</option>
+                            })
+                            .collect_view()}
+                    </optgroup>
+                </select>
+                <span>"Output folder"</span>
+                <input
+                    type="text"
+                    placeholder="Default output folder if empty"
+                    class="p-1 rounded-md"
+                    prop:value=move || new_subscription.get().options.output_dir.map(|d| d.display().to_string()).unwrap_or_default()
+                    on:change=move |ev| {
+                        let value = event_target_value(&ev);
+                        new_subscription.update(|s| s.options.output_dir = Some(value).filter(|v| !v.is_empty()).map(Into::into));
+                    }
+                />
+                <span>"Title contains"</span>
+                <input
+                    type="text"
+                    class="p-1 rounded-md"
+                    prop:value=move || new_subscription.get().filter.title_contains.unwrap_or_default()
+                    on:change=move |ev| {
+                        let value = event_target_value(&ev);
+                        new_subscription.update(|s| s.filter.title_contains = Some(value).filter(|v| !v.is_empty()));
+                    }
+                />
+                <span>"Duration (min)"</span>
+                <div class="flex space-x-1 items-center">
+                    <input
+                        type="number"
+                        min="0"
+                        placeholder="min"
+                        class="p-1 rounded-md w-24"
+                        prop:value=move || minutes(new_subscription.get().filter.min_duration)
+                        on:change=move |ev| new_subscription.update(|s| s.filter.min_duration = seconds(event_target_value(&ev)))
+                    />
+                    <span>"to"</span>
+                    <input
+                        type="number"
+                        min="0"
+                        placeholder="max"
+                        class="p-1 rounded-md w-24"
+                        prop:value=move || minutes(new_subscription.get().filter.max_duration)
+                        on:change=move |ev| new_subscription.update(|s| s.filter.max_duration = seconds(event_target_value(&ev)))
+                    />
+                </div>
+            </div>
+            <button
+                on:click=subscribe
+                disabled=busy
+                class="border-2 border-gray-500 h-8 w-52 mt-2 rounded-md bg-gray-400 hover:bg-gray-500 shadow-md"
+            >
+                "Subscribe"
+            </button>
+            <h3 class="mt-4">"Subscriptions"</h3>
+            <For
+                each=move || subscriptions.get()
+                key=|s| (s.url.clone(), s.enabled)
+                children=move |subscription| {
+                    let toggled = Subscription {
+                        enabled: !subscription.enabled,
+                        ..subscription.clone()
+                    };
+                    let url = subscription.url.clone();
+                    let format = subscription.options.output_format.clone().unwrap_or_else(|| "default format".into());
+                    view! {
+                        <div class="flex items-center space-x-2 p-1 border-b border-gray-400">
+                            <input
+                                type="checkbox"
+                                prop:checked=subscription.enabled
+                                on:change=move |_| {
+                                    let args = serde_wasm_bindgen::to_value(&ValueArgs { value: toggled.clone() }).unwrap();
+                                    run("update_subscription", args, false);
+                                }
+                            />
+                            <div class="flex flex-col flex-1 min-w-0">
+                                <span class="font-semibold truncate">{subscription.name}</span>
+                                <span class="text-sm truncate">{format!("{} ({format})", subscription.url)}</span>
+                            </div>
+                            <button
+                                on:click=move |_| {
+                                    let args = serde_wasm_bindgen::to_value(&UrlArgs { url: url.clone() }).unwrap();
+                                    run("remove_subscription", args, false);
+                                }
+                                class="border-2 border-gray-500 h-8 px-2 rounded-md bg-gray-400 hover:bg-gray-500 shadow-md"
+                            >
+                                "Remove"
+                            </button>
+                        </div>
+                    }
+                }
+            />
+        </div>
+    }
+}
+#[component]
+pub fn Statistics() -> impl IntoView {
+    view! {
+        <div class="flex items-center justify-center h-12 p-2 bg-gray-300">
+            <p>Statistics buttons here</p>
+        </div>
+        <div class="flex w-full h-full p-2">
+            <p>Statistics here</p>
+        </div>
+    }
+}
+#[component]
+pub fn App() -> impl IntoView {
+    let (state, set_state) = create_signal(MainState::Download);
+    let downloads = create_rw_signal(Vec::<Download>::new());
+    provide_context(downloads);
+    let playback = create_rw_signal(None::<Playback>);
+    provide_context(playback);
+    let notification_context = provide_notification_context();
+    let tray_context = notification_context.clone();
+    let cookie_context = notification_context.clone();
+    let cookie_prompt = provide_cookie_prompt();
+    let update_context = provide_update_context();
+    let update_context2 = update_context.clone();
+    let update_context3 = update_context.clone();
+    let (show_update, set_show_update) = create_signal(update_context.state.get().show);
+    let (update_progress, set_update_progress) = create_signal(update_context.state.get().progress);
+    create_effect(move |_| {
+        set_show_update.set(update_context.state.get().show);
+        set_update_progress.set(update_context.state.get().progress);
+    });
+    // Listen for update events from backend
+    create_effect(move |_| {
+        let update_context = update_context2.clone();
         let closure = Closure::<dyn FnMut(_)>::new(move |percent: JsValue| {
             let percent: u8 = serde_wasm_bindgen::from_value(percent).unwrap_or(0);
             update_context.state.update(|s| {
@@ -470,13 +2852,98 @@ pub fn App() -> impl IntoView {
     create_effect(move |_| {
         let closure = Closure::<dyn FnMut(_)>::new(move |s: JsValue| {
             let event: Event = serde_wasm_bindgen::from_value(s).unwrap();
-            if let EventType::Download(d_ev) = event.payload {
-                update_download_state(d_ev.id, DownloadState::Loading(d_ev.progress));
+            match event.payload {
+                EventType::Download(d_ev) => {
+                    update_download_state(d_ev.id, DownloadState::Loading(d_ev.progress));
+                }
+                EventType::State(args) => update_download_state(args.id, args.state),
+                _ => {}
             }
         });
         spawn_local(async move {
             listen("download-progress", closure.as_ref().unchecked_ref()).await;
+            listen("download-state", closure.as_ref().unchecked_ref()).await;
+            closure.forget();
+        });
+    });
+    create_effect(move |_| {
+        let closure = Closure::<dyn FnMut(_)>::new(move |s: JsValue| {
+            if let Ok(event) = serde_wasm_bindgen::from_value::<PlaybackEvent>(s) {
+                playback.set(event.payload);
+            }
+        });
+        spawn_local(async move {
+            listen("playback", closure.as_ref().unchecked_ref()).await;
+            closure.forget();
+        });
+    });
+    // Downloads enqueued by the backend, e.g. new uploads of subscriptions
+    create_effect(move |_| {
+        let closure = Closure::<dyn FnMut(_)>::new(move |s: JsValue| {
+            let event: Event = serde_wasm_bindgen::from_value(s).unwrap();
+            if let EventType::Downloads(dls) = event.payload {
+                downloads.set(dls);
+            }
+        });
+        spawn_local(async move {
+            listen("downloads-changed", closure.as_ref().unchecked_ref()).await;
+            closure.forget();
+            let dls = invoke_without_args("get_downloads").await;
+            if let Ok(dls) = serde_wasm_bindgen::from_value::<Vec<Download>>(dls) {
+                downloads.update(|d| {
+                    for download in dls {
+                        if !d.contains(&download) {
+                            d.push(download);
+                        }
+                    }
+                });
+            }
+        });
+    });
+    // Process URLs passed on the command line, either at startup or forwarded by a second instance
+    create_effect(move |_| {
+        let notification_context = notification_context.clone();
+        let forwarded_context = notification_context.clone();
+        let closure = Closure::<dyn FnMut(_)>::new(move |s: JsValue| {
+            let event: Event = serde_wasm_bindgen::from_value(s).unwrap();
+            if let EventType::Launch(launch_args) = event.payload {
+                let notification_context = forwarded_context.clone();
+                spawn_local(async move {
+                    handle_launch_args(launch_args, downloads, update_download_state, notification_context, cookie_prompt).await;
+                });
+            }
+        });
+        spawn_local(async move {
+            listen("launch-args", closure.as_ref().unchecked_ref()).await;
             closure.forget();
+            let launch_args = invoke_without_args("take_launch_args").await;
+            if let Ok(launch_args) = serde_wasm_bindgen::from_value::<LaunchArgs>(launch_args) {
+                handle_launch_args(launch_args, downloads, update_download_state, notification_context, cookie_prompt).await;
+            }
+        });
+    });
+    // Tray menu actions that run through the frontend queue
+    create_effect(move |_| {
+        let add_context = tray_context.clone();
+        let start_all_context = tray_context.clone();
+        let add_closure = Closure::<dyn FnMut(JsValue)>::new(move |_| {
+            let notification_context = add_context.clone();
+            spawn_local(async move {
+                let result = invoke("try_add", JsValue::NULL).await;
+                finish_add(result, downloads, update_download_state, notification_context, cookie_prompt).await;
+            });
+        });
+        let start_all_closure = Closure::<dyn FnMut(JsValue)>::new(move |_| {
+            let notification_context = start_all_context.clone();
+            spawn_local(async move {
+                download_queue(downloads.get_untracked(), update_download_state, notification_context).await;
+            });
+        });
+        spawn_local(async move {
+            listen("tray-add", add_closure.as_ref().unchecked_ref()).await;
+            listen("tray-start-all", start_all_closure.as_ref().unchecked_ref()).await;
+            add_closure.forget();
+            start_all_closure.forget();
         });
     });
     view! {
@@ -497,6 +2964,9 @@ pub fn App() -> impl IntoView {
                         MainState::Download => view! { <MainContent downloads update_download_state /> }.into_view(),
                         MainState::Statistics => view! { <Statistics /> }.into_view(),
                         MainState::Settings => view! { <Settings /> }.into_view(),
+                        MainState::About => view! { <About /> }.into_view(),
+                        MainState::Subscriptions => view! { <Subscriptions /> }.into_view(),
+                        MainState::Library => view! { <Library /> }.into_view(),
                     }}
                 </div>
             </div>
@@ -516,6 +2986,29 @@ pub fn App() -> impl IntoView {
                     });
                 })
             />
+            <CookieModal
+                cookie_prompt
+                on_import=Callback::new(move |_| {
+                    let notification_context = cookie_context.clone();
+                    spawn_local(async move {
+                        match invoke("import_cookies", JsValue::NULL).await {
+                            Ok(_) => {
+                                if let Some(url) = cookie_prompt.url.get_untracked() {
+                                    cookie_prompt.url.set(None);
+                                    fetch_metadata(url, downloads, notification_context, cookie_prompt).await;
+                                }
+                            }
+                            Err(err) => {
+                                let err: YaydlError = serde_wasm_bindgen::from_value(err).unwrap();
+                                notification_context.add_notification(Notification {
+                                    text: err.to_string(),
+                                    notification_type: NotificationType::Error,
+                                });
+                            }
+                        }
+                    });
+                })
+            />
             <NotificationList />
         </main>
     }
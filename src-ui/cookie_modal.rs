@@ -0,0 +1,39 @@
+use leptos::*;
+
+/// URL of the video whose metadata failed because it requires signing in.
+#[derive(Clone, Copy)]
+pub struct CookiePrompt {
+    pub url: RwSignal<Option<String>>,
+}
+
+pub fn provide_cookie_prompt() -> CookiePrompt {
+    let cookie_prompt = CookiePrompt {
+        url: create_rw_signal(None),
+    };
+    provide_context(cookie_prompt);
+    cookie_prompt
+}
+
+#[component]
+pub fn CookieModal(cookie_prompt: CookiePrompt, on_import: Callback<()>) -> impl IntoView {
+    view! {
+        <Show when=move || cookie_prompt.url.get().is_some()>
+            <div class="fixed inset-0 bg-black bg-opacity-50 flex items-center justify-center z-50">
+                <div class="bg-white rounded-lg shadow-lg p-8 flex flex-col items-center min-w-[350px] max-w-[500px]">
+                    <h2 class="text-xl font-bold mb-4">Sign-in Required</h2>
+                    <p class="mb-2 text-center">This video is members-only, age-restricted or private.</p>
+                    <p class="mb-4 text-center text-sm break-all">{move || cookie_prompt.url.get().unwrap_or_default()}</p>
+                    <p class="mb-4 text-center">Import a cookies.txt exported from a signed-in browser to retry, or pick a browser in Settings.</p>
+                    <div class="flex space-x-4 mt-4">
+                        <button class="bg-blue-600 text-white px-4 py-2 rounded hover:bg-blue-700 font-semibold" on:click=move |_| on_import.call(())>
+                            Import cookies.txt
+                        </button>
+                        <button class="bg-gray-300 text-gray-800 px-4 py-2 rounded hover:bg-gray-400 font-semibold" on:click=move |_| cookie_prompt.url.set(None)>
+                            Cancel
+                        </button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+    }
+}
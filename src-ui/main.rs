@@ -1,4 +1,5 @@
 mod app;
+mod cookie_modal;
 mod notification;
 mod update_modal;
 mod update_context;